@@ -1,5 +1,6 @@
 use std::{io, str::FromStr};
 
+use clap::{Args, ValueEnum};
 use clap2::ArgMatches;
 use derive_more::{AsRef, Deref};
 use solana_clap_utils::keypair::signer_from_path;
@@ -7,7 +8,10 @@ use solana_cli_config::{Config, CONFIG_FILE};
 use solana_client::{nonblocking, rpc_client::RpcClient};
 use solana_sdk::{
     commitment_config::{CommitmentConfig, CommitmentLevel},
-    signer::Signer,
+    hash::Hash,
+    pubkey::Pubkey,
+    signature::Signature,
+    signer::{null_signer::NullSigner, presigner::Presigner, Signer},
 };
 
 /// clap requires arg types to impl Clone, but solana doesnt do it
@@ -45,18 +49,118 @@ impl ConfigWrapper {
         )
     }
 
-    pub fn signer(&self) -> Box<dyn Signer> {
+    /// The fee payer: `--fee-payer` if provided, else the config's keypair
+    pub fn signer(&self, offline: &OfflineArgs) -> Box<dyn Signer> {
+        let path = offline.fee_payer.as_deref().unwrap_or(&self.0.keypair_path);
+        offline.signer_from_path(path)
+    }
+}
+
+/// Flags for offline signing and durable nonces, mirroring the solana CLI's
+#[derive(Args, Clone, Debug)]
+pub struct OfflineArgs {
+    #[arg(
+        long,
+        global = true,
+        help = "Fee payer keypair path, or pubkey if signing offline. Defaults to the solana CLI config's keypair"
+    )]
+    pub fee_payer: Option<String>,
+
+    #[arg(
+        long,
+        global = true,
+        requires = "blockhash",
+        help = "Sign the transaction offline and print the signatures instead of sending it",
+        default_value_t = false
+    )]
+    pub sign_only: bool,
+
+    #[arg(
+        long,
+        global = true,
+        help = "Use the supplied blockhash instead of fetching the latest one. Required for --sign-only"
+    )]
+    pub blockhash: Option<Hash>,
+
+    #[arg(
+        long,
+        global = true,
+        help = "Durable nonce account to use. If --blockhash is not provided, the nonce is fetched from this account"
+    )]
+    pub nonce: Option<Pubkey>,
+
+    #[arg(
+        long,
+        global = true,
+        requires = "nonce",
+        help = "Nonce authority keypair path, or pubkey if signing offline. Defaults to the fee payer"
+    )]
+    pub nonce_authority: Option<String>,
+
+    #[arg(
+        long = "signer",
+        global = true,
+        value_parser = parse_pubkey_signature,
+        help = "PUBKEY=SIGNATURE of a signature obtained with --sign-only. Can be repeated"
+    )]
+    pub signers: Vec<(Pubkey, Signature)>,
+
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        help = "Print the serialized unsigned transaction message in this encoding instead of signing and sending it, e.g. for importing into a multisig"
+    )]
+    pub output_tx: Option<TxEncoding>,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum TxEncoding {
+    Base58,
+    Base64,
+}
+
+impl OfflineArgs {
+    /// Resolves a keypair path or pubkey into a signer:
+    /// - pubkeys with a matching --signer are resolved into presigners
+    /// - other pubkeys are resolved into null signers if --sign-only or --output-tx
+    pub fn signer_from_path(&self, path: &str) -> Box<dyn Signer> {
+        if let Ok(pubkey) = Pubkey::from_str(path) {
+            if let Some((_, signature)) = self.signers.iter().find(|(pk, _)| *pk == pubkey) {
+                return Box::new(Presigner::new(&pubkey, signature));
+            }
+            if self.sign_only || self.output_tx.is_some() {
+                return Box::new(NullSigner::new(&pubkey));
+            }
+            panic!("Missing signature for pubkey signer {pubkey}, provide it with --signer {pubkey}=<SIGNATURE>");
+        }
         // Not supporting
         // - SignerSourceKind::Prompt with skip seed phrase validation since we dont allow that flag in crate::Args
         // - SignerSourceKind::Usb with confirm_key since we dont allow that flag in crate::Args
-        // - SignerSourceKind::Pubkey since we dont allow SIGN_ONLY_ARG in crate::Args
+        // SignerSourceKind::Pubkey is handled above.
         // See: https://docs.rs/solana-clap-utils/latest/src/solana_clap_utils/keypair.rs.html#752-820
         let empty_argmatches = ArgMatches::default();
         // This throws "Protocol("Unknown error")" if usb://ledger and ledger is not unlocked and on solana app
-        signer_from_path(&empty_argmatches, &self.0.keypair_path, "wallet", &mut None).unwrap()
+        signer_from_path(&empty_argmatches, path, "signer", &mut None).unwrap()
+    }
+
+    /// true if the transaction's blockhash was not fetched by us
+    /// and therefore must not be replaced
+    pub fn has_fixed_blockhash(&self) -> bool {
+        self.blockhash.is_some() || self.nonce.is_some()
     }
 }
 
+pub fn parse_pubkey_signature(arg: &str) -> Result<(Pubkey, Signature), String> {
+    let (pubkey, signature) = arg
+        .split_once('=')
+        .ok_or_else(|| format!("{arg} is not in the format PUBKEY=SIGNATURE"))?;
+    let pubkey = Pubkey::from_str(pubkey).map_err(|e| format!("invalid pubkey {pubkey}: {e}"))?;
+    let signature = Signature::from_str(signature)
+        .map_err(|e| format!("invalid signature {signature}: {e}"))?;
+    Ok((pubkey, signature))
+}
+
 pub fn parse_solana_cli_config_from_path(path: &str) -> Result<ConfigWrapper, io::Error> {
     let p = if path.is_empty() {
         CONFIG_FILE.as_ref().ok_or_else(|| {
@@ -70,3 +174,71 @@ pub fn parse_solana_cli_config_from_path(path: &str) -> Result<ConfigWrapper, io
     };
     Ok(ConfigWrapper(Config::load(p)?))
 }
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::signature::Keypair;
+
+    use super::*;
+
+    fn offline_args(sign_only: bool, signers: Vec<(Pubkey, Signature)>) -> OfflineArgs {
+        OfflineArgs {
+            fee_payer: None,
+            sign_only,
+            blockhash: Some(Hash::new_unique()),
+            nonce: None,
+            nonce_authority: None,
+            signers,
+            output_tx: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_pubkey_signature() {
+        let keypair = Keypair::new();
+        let signature = keypair.sign_message(b"msg");
+        assert_eq!(
+            parse_pubkey_signature(&format!("{}={}", keypair.pubkey(), signature)).unwrap(),
+            (keypair.pubkey(), signature)
+        );
+        assert!(parse_pubkey_signature(&keypair.pubkey().to_string()).is_err());
+        assert!(parse_pubkey_signature(&format!("{}=abc", keypair.pubkey())).is_err());
+        assert!(parse_pubkey_signature(&format!("abc={}", signature)).is_err());
+    }
+
+    #[test]
+    fn test_signer_from_path_presigner() {
+        let keypair = Keypair::new();
+        let other = Keypair::new();
+        let signature = keypair.sign_message(b"msg");
+        let args = offline_args(
+            false,
+            vec![
+                (other.pubkey(), other.sign_message(b"msg")),
+                (keypair.pubkey(), signature),
+            ],
+        );
+        let signer = args.signer_from_path(&keypair.pubkey().to_string());
+        assert_eq!(signer.pubkey(), keypair.pubkey());
+        assert_eq!(signer.try_sign_message(b"msg").unwrap(), signature);
+        // a presigner cant sign a different message
+        assert!(signer.try_sign_message(b"other msg").is_err());
+    }
+
+    #[test]
+    fn test_signer_from_path_sign_only() {
+        let pubkey = Pubkey::new_unique();
+        let signer = offline_args(true, vec![]).signer_from_path(&pubkey.to_string());
+        assert_eq!(signer.pubkey(), pubkey);
+        assert_eq!(
+            signer.try_sign_message(b"msg").unwrap(),
+            Signature::default()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Missing signature for pubkey signer")]
+    fn test_signer_from_path_missing_signature() {
+        offline_args(false, vec![]).signer_from_path(&Pubkey::new_unique().to_string());
+    }
+}
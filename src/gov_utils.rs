@@ -0,0 +1,307 @@
+use std::process;
+
+use base64::{engine::general_purpose, Engine};
+use borsh::ser::BorshSerialize;
+use clap::Args;
+use solana_client::rpc_client::RpcClient;
+use solana_program::borsh::try_from_slice_unchecked;
+use solana_sdk::{instruction::Instruction, packet::PACKET_DATA_SIZE, pubkey, pubkey::Pubkey};
+use spl_governance::{
+    instruction::{create_proposal, insert_transaction, sign_off_proposal},
+    state::{
+        governance::GovernanceV2,
+        native_treasury::get_native_treasury_address,
+        proposal::{get_proposal_address, VoteType},
+        proposal_transaction::{AccountMetaData, InstructionData},
+        realm::RealmV2,
+        token_owner_record::get_token_owner_record_address,
+    },
+};
+
+use crate::tx_utils::{build_tx, max_tx_len, send_or_sim_tx, TxSigners};
+
+pub const DEFAULT_GOVERNANCE_PROGRAM: Pubkey =
+    pubkey!("GovER5Lthms3bLBqWub97yVrMmEogzX7xNjdXpPPCVZw");
+
+/// Flags for creating a spl-governance proposal out of the admin instructions
+/// instead of only printing them
+#[derive(Args, Debug)]
+pub struct ProposalArgs {
+    #[arg(
+        long,
+        global = true,
+        help = "Create, populate and sign off a spl-governance proposal executing the instructions, with the configured signer as the proposal owner. Cannot be used with --sign-only or --output-tx",
+        default_value_t = false
+    )]
+    pub propose: bool,
+
+    #[arg(
+        long,
+        global = true,
+        help = "Governance account to create the proposal on. Defaults to the admin authority, which is the governance account if the upgrade authority was transferred to it"
+    )]
+    pub governance: Option<Pubkey>,
+
+    #[arg(
+        long,
+        global = true,
+        help = "spl-governance program the governance account belongs to",
+        default_value_t = DEFAULT_GOVERNANCE_PROGRAM
+    )]
+    pub governance_program: Pubkey,
+
+    #[arg(
+        long,
+        global = true,
+        help = "Create the proposal with the realm's council token owner record instead of the community one",
+        default_value_t = false
+    )]
+    pub council: bool,
+
+    #[arg(
+        long,
+        global = true,
+        help = "Name of the proposal. Defaults to a summary of the instructions"
+    )]
+    pub proposal_name: Option<String>,
+
+    #[arg(
+        long,
+        global = true,
+        help = "Description link of the proposal",
+        default_value = ""
+    )]
+    pub description_link: String,
+}
+
+pub fn to_instruction_data(ix: Instruction) -> InstructionData {
+    InstructionData {
+        program_id: ix.program_id,
+        accounts: ix
+            .accounts
+            .iter()
+            .map(|acc| AccountMetaData {
+                pubkey: acc.pubkey,
+                is_signer: acc.is_signer,
+                is_writable: acc.is_writable,
+            })
+            .collect(),
+        data: ix.data,
+    }
+}
+
+/// base64-encoded borsh serialization, the format the Realms UI accepts
+pub fn encode_instruction_data(ix_data: &InstructionData) -> String {
+    let serialized_data: Vec<u8> = ix_data.try_to_vec().unwrap();
+    general_purpose::STANDARD.encode(serialized_data)
+}
+
+/// Creates a proposal with one proposal transaction per element of `transactions`
+/// on the `--governance` account, or `admin` if not provided, then signs it off so that voting starts.
+///
+/// Each governance instruction is sent in its own transaction since
+/// InsertTransaction instructions carry the full instruction data and can get large.
+/// The transactions depend on each other and the proposal address depends on the governance's
+/// proposal count at the time of creation, so they can't be signed offline for later submission.
+///
+/// Returns the proposal's address.
+pub fn propose(
+    args: &crate::Args,
+    proposal_args: &ProposalArgs,
+    admin: &Pubkey,
+    default_name: &str,
+    transactions: Vec<Vec<InstructionData>>,
+) -> Pubkey {
+    if args.offline.sign_only || args.offline.output_tx.is_some() {
+        args.output.log(
+            "[FAIL] --propose sends several dependent transactions and cannot be used with --sign-only or --output-tx. Submit the printed instructions through another proposal tool instead",
+        );
+        process::exit(1);
+    }
+    let client = args.config.rpc_client();
+    let program_id = proposal_args.governance_program;
+    let governance_addr = proposal_args.governance.unwrap_or(*admin);
+
+    let governance_acc = client.get_account(&governance_addr).unwrap();
+    if governance_acc.owner != program_id {
+        args.output.log(format!(
+            "[FAIL] {} is not a governance account of {}. Provide the admin authority's governance with --governance",
+            governance_addr, program_id
+        ));
+        process::exit(1);
+    }
+    let governance: GovernanceV2 = try_from_slice_unchecked(&governance_acc.data).unwrap();
+    let native_treasury = get_native_treasury_address(&program_id, &governance_addr);
+    if *admin != governance_addr && *admin != native_treasury {
+        args.output.log(format!(
+            "[WARN] Admin authority {} is neither governance {} nor its native treasury {}. The proposal will fail to execute.",
+            admin, governance_addr, native_treasury
+        ));
+    }
+    let realm: RealmV2 =
+        try_from_slice_unchecked(&client.get_account_data(&governance.realm).unwrap()).unwrap();
+    let governing_token_mint = match proposal_args.council {
+        true => realm
+            .config
+            .council_mint
+            .expect("realm has no council mint"),
+        false => realm.community_mint,
+    };
+
+    let payer = args.config.signer(&args.offline);
+    let owner = payer.pubkey();
+    let signers = TxSigners::new(args, payer);
+    let token_owner_record = get_token_owner_record_address(
+        &program_id,
+        &governance.realm,
+        &governing_token_mint,
+        &owner,
+    );
+    let proposal_index = governance.proposals_count;
+    let proposal = get_proposal_address(
+        &program_id,
+        &governance_addr,
+        &governing_token_mint,
+        &proposal_index.to_le_bytes(),
+    );
+
+    let name = proposal_args
+        .proposal_name
+        .clone()
+        .unwrap_or_else(|| default_name.to_owned());
+    args.output
+        .log(format!("Creating proposal {} \"{}\"", proposal, name));
+    let ix = create_proposal(
+        &program_id,
+        &governance_addr,
+        &token_owner_record,
+        &owner,
+        &owner,
+        None,
+        &governance.realm,
+        name,
+        proposal_args.description_link.clone(),
+        &governing_token_mint,
+        VoteType::SingleChoice,
+        vec!["Approve".to_owned()],
+        true,
+        proposal_index,
+    );
+    send_gov_ix(args, &client, ix, &signers);
+
+    for (index, instructions) in transactions.into_iter().enumerate() {
+        args.output
+            .log(format!("Inserting proposal transaction {}", index));
+        let ix = insert_transaction(
+            &program_id,
+            &governance_addr,
+            &proposal,
+            &token_owner_record,
+            &owner,
+            &owner,
+            0,
+            index.try_into().unwrap(),
+            governance.config.min_transaction_hold_up_time,
+            instructions,
+        );
+        send_gov_ix(args, &client, ix, &signers);
+    }
+
+    args.output.log("Signing off proposal");
+    let ix = sign_off_proposal(
+        &program_id,
+        &governance.realm,
+        &governance_addr,
+        &proposal,
+        &owner,
+        Some(&token_owner_record),
+    );
+    send_gov_ix(args, &client, ix, &signers);
+
+    proposal
+}
+
+fn send_gov_ix(args: &crate::Args, client: &RpcClient, ix: Instruction, signers: &TxSigners) {
+    let tx = build_tx(args, client, &[ix], signers);
+    send_or_sim_tx(args, client, &tx, signers);
+}
+
+/// Splits `instructions` into as few proposal transactions as possible
+/// such that the transaction inserting each one still fits in a packet
+pub fn pack_proposal_transactions(instructions: Vec<InstructionData>) -> Vec<Vec<InstructionData>> {
+    let mut res = Vec::new();
+    let mut current = Vec::new();
+    for ix in instructions {
+        current.push(ix);
+        if current.len() > 1 && insert_transaction_tx_len(&current) > PACKET_DATA_SIZE {
+            let ix = current.pop().unwrap();
+            res.push(std::mem::replace(&mut current, vec![ix]));
+        }
+    }
+    if !current.is_empty() {
+        res.push(current);
+    }
+    res
+}
+
+/// Upper bound of the serialized size of the transaction inserting `instructions`
+/// as a single proposal transaction
+fn insert_transaction_tx_len(instructions: &[InstructionData]) -> usize {
+    let [program_id, governance, proposal, token_owner_record, owner] =
+        [(); 5].map(|_| Pubkey::new_unique());
+    let ix = insert_transaction(
+        &program_id,
+        &governance,
+        &proposal,
+        &token_owner_record,
+        &owner,
+        &owner,
+        0,
+        u16::MAX,
+        u32::MAX,
+        instructions.to_vec(),
+    );
+    max_tx_len(&[ix], &owner)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn withdraw_fees_like_ix() -> InstructionData {
+        InstructionData {
+            program_id: stakedex_interface::ID,
+            accounts: (0..5)
+                .map(|i| AccountMetaData {
+                    pubkey: Pubkey::new_unique(),
+                    is_signer: i == 0,
+                    is_writable: i > 1,
+                })
+                .collect(),
+            data: vec![0],
+        }
+    }
+
+    #[test]
+    fn test_pack_proposal_transactions() {
+        assert!(pack_proposal_transactions(vec![]).is_empty());
+
+        let ixs: Vec<_> = (0..12).map(|_| withdraw_fees_like_ix()).collect();
+        let packed = pack_proposal_transactions(ixs.clone());
+        assert!(packed.len() > 1);
+        assert!(packed.len() < ixs.len());
+        assert_eq!(packed.concat(), ixs);
+        for txs in packed.iter() {
+            assert!(insert_transaction_tx_len(txs) <= PACKET_DATA_SIZE);
+        }
+    }
+
+    #[test]
+    fn test_pack_oversized_instruction() {
+        let mut ix = withdraw_fees_like_ix();
+        ix.data = vec![0; PACKET_DATA_SIZE];
+        let packed = pack_proposal_transactions(vec![ix.clone(), ix]);
+        // each oversized instruction still gets its own proposal transaction
+        assert_eq!(packed.len(), 2);
+    }
+}
@@ -1,11 +1,22 @@
+use std::path::PathBuf;
+
 use clap::{builder::ValueParser, Parser};
 
 mod argparse;
 mod global_lut_cache;
+mod gov_utils;
+mod mint_registry;
+mod output;
+mod pool_utils;
+mod stakedex_utils;
 mod subcmd;
+mod token_utils;
 mod tx_utils;
 
-use argparse::{parse_solana_cli_config_from_path, ConfigWrapper};
+use argparse::{parse_solana_cli_config_from_path, ConfigWrapper, OfflineArgs};
+use mint_registry::MintRegistry;
+use output::OutputFormat;
+use solana_sdk::pubkey::Pubkey;
 use subcmd::{Subcmd, SubcmdExec};
 
 #[derive(Parser, Debug)]
@@ -32,10 +43,66 @@ pub struct Args {
     )]
     pub dry_run: bool,
 
+    #[arg(
+        long,
+        help = "percentile of recent prioritization fees paid for the transaction's writable accounts to set as priority fee",
+        default_value_t = 50,
+        value_parser = clap::value_parser!(u8).range(0..=100)
+    )]
+    pub priority_fee_percentile: u8,
+
+    #[arg(
+        long,
+        help = "max priority fee to pay, in micro-lamports per compute unit",
+        default_value_t = 100_000
+    )]
+    pub priority_fee_cap: u64,
+
+    #[arg(
+        long,
+        help = "compute unit limit to set instead of simulating. Defaults to the max limit if the blockhash is fixed by --blockhash"
+    )]
+    pub compute_unit_limit: Option<u32>,
+
+    #[arg(
+        long,
+        help = "compute unit price in micro-lamports to set instead of using recent prioritization fees. Defaults to 0 if the blockhash is fixed by --blockhash"
+    )]
+    pub compute_unit_price: Option<u64>,
+
+    #[arg(
+        long,
+        help = "admin authority to build admin instructions for and verify them against. Defaults to the stakedex program's upgrade authority"
+    )]
+    pub admin: Option<Pubkey>,
+
+    #[arg(
+        long,
+        help = "path to TOML or JSON file of mints to add to, or replace in, the bundled mint registry"
+    )]
+    pub mints_file: Option<PathBuf>,
+
+    #[arg(
+        long,
+        help = "format to print results in. Progress messages are printed to stderr for formats other than text",
+        value_enum,
+        default_value_t = OutputFormat::Text
+    )]
+    pub output: OutputFormat,
+
+    #[command(flatten)]
+    pub offline: OfflineArgs,
+
     #[command(subcommand)]
     pub subcmd: Subcmd,
 }
 
+impl Args {
+    pub fn mint_registry(&self) -> MintRegistry {
+        MintRegistry::load(self.mints_file.as_ref()).unwrap()
+    }
+}
+
 fn main() {
     let args = Args::parse();
     args.subcmd.process_cmd(&args);
@@ -0,0 +1,234 @@
+use std::{collections::HashSet, error::Error, fs, path::Path, str::FromStr};
+
+use serde::{Deserialize, Deserializer};
+use solana_sdk::pubkey::Pubkey;
+
+/// Registry bundled with the CLI
+const BUNDLED_MINTS_TOML: &str = include_str!("mints.toml");
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct MintInfo {
+    #[serde(deserialize_with = "deserialize_pubkey")]
+    pub mint: Pubkey,
+
+    pub symbol: String,
+
+    pub decimals: u8,
+
+    /// Program that owns `mint`, spl-token if not set
+    #[serde(
+        default = "default_token_program",
+        deserialize_with = "deserialize_pubkey"
+    )]
+    pub token_program: Pubkey,
+
+    /// Program that owns `pool`. None for mints without a stake pool, e.g. wSOL
+    #[serde(default, deserialize_with = "deserialize_opt_pubkey")]
+    pub pool_program: Option<Pubkey>,
+
+    /// Stake pool (or Marinade/Lido state) account
+    #[serde(default, deserialize_with = "deserialize_opt_pubkey")]
+    pub pool: Option<Pubkey>,
+}
+
+impl MintInfo {
+    /// (pool_program, pool). None if the mint has no stake pool,
+    /// an error if only one of the two is registered
+    pub fn registered_pool(&self) -> Result<Option<(Pubkey, Pubkey)>, String> {
+        match (self.pool_program, self.pool) {
+            (Some(pool_program), Some(pool)) => Ok(Some((pool_program, pool))),
+            (None, None) => Ok(None),
+            _ => Err(format!(
+                "{}: pool_program and pool must be registered together",
+                self.symbol
+            )),
+        }
+    }
+}
+
+/// The xSOL mints stakedex supports, replacing the CLI's
+/// previously compiled list so that new pools dont need a release
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+pub struct MintRegistry {
+    pub mints: Vec<MintInfo>,
+}
+
+impl MintRegistry {
+    pub fn bundled() -> Self {
+        Self::from_toml(BUNDLED_MINTS_TOML).unwrap()
+    }
+
+    /// The bundled registry, extended by the file at `path` if provided.
+    /// Entries in the file replace bundled entries of the same mint.
+    pub fn load<P: AsRef<Path>>(path: Option<P>) -> Result<Self, Box<dyn Error>> {
+        let mut res = Self::bundled();
+        if let Some(path) = path {
+            res.merge(Self::from_file(path)?);
+        }
+        Ok(res)
+    }
+
+    /// TOML or JSON (if the file has a .json extension) of
+    /// `{ "mints": [{ "mint", "symbol", "decimals", "token_program"?, "pool_program"?, "pool"? }] }`
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+        let s = fs::read_to_string(&path)?;
+        match path.as_ref().extension().and_then(|e| e.to_str()) {
+            Some("json") => Self::from_json(&s),
+            _ => Self::from_toml(&s),
+        }
+    }
+
+    pub fn from_toml(s: &str) -> Result<Self, Box<dyn Error>> {
+        let res: Self = toml::from_str(s)?;
+        res.check_duplicates()?;
+        Ok(res)
+    }
+
+    pub fn from_json(s: &str) -> Result<Self, Box<dyn Error>> {
+        let res: Self = serde_json::from_str(s)?;
+        res.check_duplicates()?;
+        Ok(res)
+    }
+
+    fn check_duplicates(&self) -> Result<(), Box<dyn Error>> {
+        let mut seen = HashSet::new();
+        for info in self.mints.iter() {
+            if !seen.insert(info.mint) {
+                return Err(format!("duplicate mint {} in registry", info.mint).into());
+            }
+        }
+        Ok(())
+    }
+
+    fn merge(&mut self, other: Self) {
+        for info in other.mints {
+            match self.mints.iter_mut().find(|i| i.mint == info.mint) {
+                Some(existing) => *existing = info,
+                None => self.mints.push(info),
+            }
+        }
+    }
+
+    pub fn get(&self, mint: &Pubkey) -> Option<&MintInfo> {
+        self.mints.iter().find(|i| i.mint == *mint)
+    }
+
+    pub fn mints(&self) -> Vec<Pubkey> {
+        self.mints.iter().map(|i| i.mint).collect()
+    }
+}
+
+fn default_token_program() -> Pubkey {
+    spl_token::ID
+}
+
+fn deserialize_pubkey<'de, D: Deserializer<'de>>(d: D) -> Result<Pubkey, D::Error> {
+    let s = String::deserialize(d)?;
+    Pubkey::from_str(&s).map_err(serde::de::Error::custom)
+}
+
+fn deserialize_opt_pubkey<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Pubkey>, D::Error> {
+    Option::<String>::deserialize(d)?
+        .map(|s| Pubkey::from_str(&s).map_err(serde::de::Error::custom))
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use stakedex_sdk_common::{
+        bsol, cogentsol, daosol, esol, jitosol, jsol, lainesol, msol, risksol, scnsol, stsol,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_bundled_matches_sdk() {
+        let registry = MintRegistry::bundled();
+        let mut mints = registry.mints();
+        mints.sort();
+        let mut expected = vec![
+            bsol::ID,
+            cogentsol::ID,
+            daosol::ID,
+            esol::ID,
+            jitosol::ID,
+            jsol::ID,
+            lainesol::ID,
+            msol::ID,
+            risksol::ID,
+            scnsol::ID,
+            spl_token::native_mint::ID,
+            stsol::ID,
+        ];
+        expected.sort();
+        assert_eq!(mints, expected);
+        assert_eq!(registry.get(&msol::ID).unwrap().symbol, "mSOL");
+        for info in registry.mints.iter() {
+            assert!(info.registered_pool().is_ok(), "{}", info.symbol);
+        }
+    }
+
+    #[test]
+    fn test_registered_pool() {
+        let mut info = MintInfo {
+            mint: Pubkey::new_unique(),
+            symbol: "xSOL".into(),
+            decimals: 9,
+            token_program: spl_token::ID,
+            pool_program: None,
+            pool: None,
+        };
+        assert_eq!(info.registered_pool(), Ok(None));
+        info.pool_program = Some(spl_stake_pool::id());
+        assert!(info.registered_pool().is_err());
+        let pool = Pubkey::new_unique();
+        info.pool = Some(pool);
+        assert_eq!(
+            info.registered_pool(),
+            Ok(Some((spl_stake_pool::id(), pool)))
+        );
+        info.pool_program = None;
+        assert!(info.registered_pool().is_err());
+    }
+
+    #[test]
+    fn test_merge_file() {
+        let mut registry = MintRegistry::bundled();
+        let n_bundled = registry.mints.len();
+        let new_mint = Pubkey::new_unique();
+        let json = format!(
+            r#"{{ "mints": [
+                {{ "mint": "{}", "symbol": "MSOL", "decimals": 9 }},
+                {{ "mint": "{}", "symbol": "newSOL", "decimals": 6, "token_program": "{}", "pool": "{}" }}
+            ] }}"#,
+            stakedex_sdk_common::msol::ID,
+            new_mint,
+            spl_token_2022::ID,
+            Pubkey::new_unique(),
+        );
+        registry.merge(MintRegistry::from_json(&json).unwrap());
+        assert_eq!(registry.mints.len(), n_bundled + 1);
+        let msol = registry.get(&stakedex_sdk_common::msol::ID).unwrap();
+        assert_eq!(msol.symbol, "MSOL");
+        assert_eq!(msol.pool, None);
+        assert_eq!(msol.token_program, spl_token::ID);
+        let new = registry.get(&new_mint).unwrap();
+        assert_eq!(new.decimals, 6);
+        assert_eq!(new.token_program, spl_token_2022::ID);
+        assert!(new.pool.is_some());
+        assert!(new.pool_program.is_none());
+    }
+
+    #[test]
+    fn test_invalid_registry() {
+        let dup = format!(
+            "[[mints]]\nmint = \"{0}\"\nsymbol = \"a\"\ndecimals = 9\n\n[[mints]]\nmint = \"{0}\"\nsymbol = \"b\"\ndecimals = 9\n",
+            Pubkey::new_unique()
+        );
+        assert!(MintRegistry::from_toml(&dup).is_err());
+        assert!(MintRegistry::from_toml(
+            "[[mints]]\nmint = \"abc\"\nsymbol = \"a\"\ndecimals = 9\n"
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,158 @@
+use std::fmt::Display;
+
+use clap::ValueEnum;
+use serde::Serialize;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable
+    #[default]
+    Text,
+
+    /// Pretty-printed JSON
+    Json,
+
+    /// Single-line JSON
+    JsonCompact,
+
+    /// CSV with a header row
+    Csv,
+}
+
+impl OutputFormat {
+    /// Prints progress and diagnostic messages.
+    /// Goes to stderr for machine-readable formats so that stdout only contains the result.
+    pub fn log(&self, msg: impl Display) {
+        match self {
+            Self::Text => println!("{msg}"),
+            _ => eprintln!("{msg}"),
+        }
+    }
+}
+
+/// Result of a subcommand, printed in the `--output` format.
+///
+/// Field names of the serialized result are part of the CLI's interface,
+/// so renaming them is a breaking change.
+pub trait CmdOutput: Serialize {
+    /// Row type of the CSV output. Must be flat, i.e. only scalars and options of scalars.
+    type Row: Serialize;
+
+    /// Human-readable form for `--output text`. Nothing is printed if empty.
+    ///
+    /// Quotes and progress are logged with [`OutputFormat::log`] as they happen and not repeated here,
+    /// so subcommands that send transactions only report their result, if anything.
+    fn text(&self) -> String;
+
+    fn csv_rows(&self) -> Vec<&Self::Row>;
+}
+
+pub fn print_output<T: CmdOutput>(format: OutputFormat, output: &T) {
+    let rendered = render(format, output);
+    if !rendered.is_empty() {
+        println!("{rendered}");
+    }
+}
+
+pub fn render<T: CmdOutput>(format: OutputFormat, output: &T) -> String {
+    match format {
+        OutputFormat::Text => output.text(),
+        OutputFormat::Json => serde_json::to_string_pretty(output).unwrap(),
+        OutputFormat::JsonCompact => serde_json::to_string(output).unwrap(),
+        OutputFormat::Csv => to_csv(&output.csv_rows()),
+    }
+}
+
+/// Header row followed by `rows`, without a trailing newline. Empty if `rows` is empty.
+fn to_csv<R: Serialize>(rows: &[R]) -> String {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    for row in rows {
+        writer.serialize(row).unwrap();
+    }
+    let mut res = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+    if res.ends_with('\n') {
+        res.pop();
+    }
+    res
+}
+
+/// Sorted top-level JSON keys and the CSV header of `output`, joined by ",".
+/// `output` must have at least one CSV row.
+#[cfg(test)]
+pub fn field_names<T: CmdOutput>(output: &T) -> [String; 2] {
+    let json = match serde_json::to_value(output).unwrap() {
+        serde_json::Value::Object(map) => {
+            let mut keys: Vec<_> = map.keys().map(String::as_str).collect();
+            keys.sort_unstable();
+            keys.join(",")
+        }
+        v => panic!("not a JSON object: {v}"),
+    };
+    let csv = render(OutputFormat::Csv, output);
+    let header = csv.lines().next().expect("no CSV rows").to_owned();
+    [json, header]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize)]
+    struct TestRow {
+        name: String,
+        amount: u64,
+        decimals: Option<u8>,
+    }
+
+    #[derive(Serialize)]
+    struct TestOutput {
+        rows: Vec<TestRow>,
+    }
+
+    impl CmdOutput for TestOutput {
+        type Row = TestRow;
+
+        fn text(&self) -> String {
+            self.rows
+                .iter()
+                .map(|r| r.name.as_str())
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+
+        fn csv_rows(&self) -> Vec<&TestRow> {
+            self.rows.iter().collect()
+        }
+    }
+
+    fn test_output() -> TestOutput {
+        TestOutput {
+            rows: vec![
+                TestRow {
+                    name: "a".into(),
+                    amount: 1,
+                    decimals: Some(9),
+                },
+                TestRow {
+                    name: "b, \"c\"".into(),
+                    amount: 2,
+                    decimals: None,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let output = test_output();
+        assert_eq!(render(OutputFormat::Text, &output), "a\nb, \"c\"");
+        assert_eq!(
+            render(OutputFormat::JsonCompact, &output),
+            r#"{"rows":[{"name":"a","amount":1,"decimals":9},{"name":"b, \"c\"","amount":2,"decimals":null}]}"#
+        );
+        assert_eq!(
+            render(OutputFormat::Csv, &output),
+            "name,amount,decimals\na,1,9\n\"b, \"\"c\"\"\",2,"
+        );
+    }
+}
@@ -0,0 +1,225 @@
+use std::error::Error;
+
+use borsh::BorshDeserialize;
+use lido::state::Lido;
+use marinade_finance_interface::{State as MarinadeState, STATE_ACCOUNT_DISCM};
+use solana_client::rpc_client::RpcClient;
+use solana_program::borsh::try_from_slice_unchecked;
+use solana_sdk::{account::Account, pubkey, pubkey::Pubkey};
+use spl_stake_pool::state::StakePool;
+
+use crate::{mint_registry::MintInfo, token_utils::get_multiple_accounts_chunked};
+
+pub const MARINADE_PROGRAM: Pubkey = marinade_finance_interface::ID;
+
+pub const LIDO_PROGRAM: Pubkey = pubkey!("CrX7kMhLC3cSsXJdT7JDgqrRVWGnUpX3gfEfxxU2NVLi");
+
+/// Socean's fork of the spl stake pool program, with the same StakePool layout
+pub const SOCEAN_PROGRAM: Pubkey = pubkey!("5ocnV1qiCgaQR8Jb8xWnVbApfaygJ8tNoZfgPwsgx9kx");
+
+/// Eversol's fork of the spl stake pool program, read with the spl StakePool layout
+pub const EVERSOL_PROGRAM: Pubkey = pubkey!("EverSFw9uN5t1V8kS3ficHUcKffSjwpGzUSGd7mgmSks");
+
+/// The spl stake pool program and the forks of it stakedex swaps through
+pub const SPL_STAKE_POOL_PROGRAMS: &[Pubkey] =
+    &[spl_stake_pool::ID, SOCEAN_PROGRAM, EVERSOL_PROGRAM];
+
+/// marinade's msol_price is SOL per mSOL * 2^32
+const MARINADE_PRICE_DENOMINATOR: u64 = 1 << 32;
+
+/// Value of a stake pool's tokens in SOL
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExchangeRate {
+    pub lamports: u64,
+    pub pool_tokens: u64,
+}
+
+impl ExchangeRate {
+    /// wSOL
+    pub const ONE: Self = Self {
+        lamports: 1,
+        pool_tokens: 1,
+    };
+
+    /// Lamports `pool_tokens` atomics are worth, rounded down.
+    /// 0 if the pool has no tokens
+    pub fn lamports_for(&self, pool_tokens: u64) -> u64 {
+        if self.pool_tokens == 0 {
+            return 0;
+        }
+        (u128::from(pool_tokens) * u128::from(self.lamports) / u128::from(self.pool_tokens))
+            .try_into()
+            .unwrap_or(u64::MAX)
+    }
+}
+
+/// Reads the exchange rate out of a stake pool account's data, based on the program that owns it:
+/// - spl stake pool and its forks in [`SPL_STAKE_POOL_PROGRAMS`]: StakePool.total_lamports / pool_token_supply
+/// - marinade: State.msol_price
+/// - Lido: Lido.exchange_rate, as of the last epoch it was updated
+///
+/// An error for any other program, whose layout is unknown
+pub fn exchange_rate_of(
+    pool_program: &Pubkey,
+    data: &[u8],
+) -> Result<ExchangeRate, Box<dyn Error>> {
+    match *pool_program {
+        p if SPL_STAKE_POOL_PROGRAMS.contains(&p) => {
+            let pool = try_from_slice_unchecked::<StakePool>(data)?;
+            if !pool.is_valid() {
+                return Err("not a StakePool account".into());
+            }
+            Ok(ExchangeRate {
+                lamports: pool.total_lamports,
+                pool_tokens: pool.pool_token_supply,
+            })
+        }
+        MARINADE_PROGRAM => {
+            if data.get(..8) != Some(&STATE_ACCOUNT_DISCM[..]) {
+                return Err("not a marinade State account".into());
+            }
+            let state = MarinadeState::deserialize(&mut &data[8..])?;
+            Ok(ExchangeRate {
+                lamports: state.msol_price,
+                pool_tokens: MARINADE_PRICE_DENOMINATOR,
+            })
+        }
+        LIDO_PROGRAM => {
+            let lido = try_from_slice_unchecked::<Lido>(data)?;
+            Ok(ExchangeRate {
+                lamports: lido.exchange_rate.sol_balance.0,
+                pool_tokens: lido.exchange_rate.st_sol_supply.0,
+            })
+        }
+        _ => Err(format!("unknown pool program {pool_program}").into()),
+    }
+}
+
+/// Exchange rates of each of `mints`, fetching all pools in a single batch.
+///
+/// wSOL's is [`ExchangeRate::ONE`]. An error for mints without a registered pool
+/// or whose pool could not be fetched or read.
+pub fn fetch_exchange_rates(
+    client: &RpcClient,
+    mints: &[MintInfo],
+) -> Result<Vec<Result<ExchangeRate, String>>, Box<dyn Error>> {
+    let pools: Vec<_> = mints
+        .iter()
+        .map(|info| info.pool.unwrap_or_default())
+        .collect();
+    let fetched = get_multiple_accounts_chunked(client, &pools)?;
+    Ok(mints
+        .iter()
+        .zip(fetched)
+        .map(|(info, opt)| {
+            if info.mint == spl_token::native_mint::ID {
+                return Ok(ExchangeRate::ONE);
+            }
+            registered_pool_rate(info, opt.as_ref())
+        })
+        .collect())
+}
+
+/// Exchange rate of `info`'s registered pool out of its fetched account `pool_acc`,
+/// which must be owned by the registered pool program
+fn registered_pool_rate(
+    info: &MintInfo,
+    pool_acc: Option<&Account>,
+) -> Result<ExchangeRate, String> {
+    let (pool_program, pool) = info
+        .registered_pool()?
+        .ok_or_else(|| format!("no pool registered for {}", info.symbol))?;
+    let acc = pool_acc.ok_or_else(|| format!("pool {} of {} not found", pool, info.symbol))?;
+    if acc.owner != pool_program {
+        return Err(format!(
+            "pool {} of {} is owned by {}, not its registered pool program {}",
+            pool, info.symbol, acc.owner, pool_program
+        ));
+    }
+    exchange_rate_of(&pool_program, &acc.data)
+        .map_err(|e| format!("pool {} of {}: {}", pool, info.symbol, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use borsh::BorshSerialize;
+    use spl_stake_pool::state::AccountType;
+
+    use super::*;
+
+    fn stake_pool_data(account_type: AccountType) -> Vec<u8> {
+        StakePool {
+            account_type,
+            total_lamports: 1_100_000_000,
+            pool_token_supply: 1_000_000_000,
+            ..Default::default()
+        }
+        .try_to_vec()
+        .unwrap()
+    }
+
+    #[test]
+    fn test_exchange_rate_of() {
+        let spl = stake_pool_data(AccountType::StakePool);
+        for program in SPL_STAKE_POOL_PROGRAMS {
+            let rate = exchange_rate_of(program, &spl).unwrap();
+            assert_eq!(rate.lamports_for(2_000_000_000), 2_200_000_000);
+        }
+        assert!(exchange_rate_of(
+            &spl_stake_pool::id(),
+            &stake_pool_data(AccountType::Uninitialized)
+        )
+        .is_err());
+        // layout of unknown programs isnt guessed
+        assert!(exchange_rate_of(&Pubkey::new_unique(), &spl).is_err());
+        assert!(exchange_rate_of(&MARINADE_PROGRAM, &spl).is_err());
+        assert!(exchange_rate_of(&LIDO_PROGRAM, &[0; 8]).is_err());
+    }
+
+    #[test]
+    fn test_registered_pool_rate() {
+        let pool = Pubkey::new_unique();
+        let info = MintInfo {
+            mint: Pubkey::new_unique(),
+            symbol: "xSOL".into(),
+            decimals: 9,
+            token_program: spl_token::ID,
+            pool_program: Some(EVERSOL_PROGRAM),
+            pool: Some(pool),
+        };
+        let acc = |owner: Pubkey| Account {
+            lamports: 1,
+            data: stake_pool_data(AccountType::StakePool),
+            owner,
+            executable: false,
+            rent_epoch: 0,
+        };
+        let rate = registered_pool_rate(&info, Some(&acc(EVERSOL_PROGRAM))).unwrap();
+        assert_eq!(rate.lamports_for(10), 11);
+        // same layout, but not the program the pool is registered with
+        assert!(registered_pool_rate(&info, Some(&acc(spl_stake_pool::ID))).is_err());
+        assert!(registered_pool_rate(&info, None).is_err());
+        let unregistered = MintInfo {
+            pool_program: None,
+            pool: None,
+            ..info
+        };
+        assert!(registered_pool_rate(&unregistered, Some(&acc(EVERSOL_PROGRAM))).is_err());
+    }
+
+    #[test]
+    fn test_lamports_for() {
+        assert_eq!(ExchangeRate::ONE.lamports_for(u64::MAX), u64::MAX);
+        let empty = ExchangeRate {
+            lamports: 0,
+            pool_tokens: 0,
+        };
+        assert_eq!(empty.lamports_for(5), 0);
+        let rate = ExchangeRate {
+            lamports: 3,
+            pool_tokens: 2,
+        };
+        assert_eq!(rate.lamports_for(3), 4);
+        assert_eq!(rate.lamports_for(u64::MAX), u64::MAX);
+    }
+}
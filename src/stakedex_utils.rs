@@ -0,0 +1,295 @@
+use std::{collections::HashMap, error::Error};
+
+use jupiter_amm_interface::{QuoteParams, SwapMode};
+use serde::Serialize;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    account::Account, instruction::Instruction, pubkey::Pubkey, rent::Rent,
+    stake::state::StakeState,
+};
+use stakedex_interface::{deposit_stake_ix, DepositStakeIxArgs, DepositStakeKeys};
+use stakedex_sdk::Stakedex;
+use stakedex_sdk_common::{
+    find_fee_token_acc, DepositStake, DepositStakeInfo, DepositStakeQuote, WithdrawStakeQuote,
+};
+
+use crate::token_utils::get_multiple_accounts_chunked;
+
+/// The stakedex instruction a swap is executed with
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub enum Route {
+    /// wSOL in: deposit SOL into a stake account, then deposit the stake account into the output pool
+    StakeWrappedSol,
+
+    /// xSOL in: withdraw a stake account from the input pool, then deposit it into the output pool
+    SwapViaStake,
+
+    /// Stake account in: deposit it into the output pool
+    DepositStake,
+}
+
+/// Expected result of swapping through a [`Route`], in token atomics
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RouteQuote {
+    pub route: Route,
+    pub in_amount: u64,
+    pub out_amount: u64,
+    pub fee_amount: u64,
+    pub fee_mint: Pubkey,
+}
+
+/// Initializes the stakedex SDK with all the pool accounts it needs for quoting,
+/// warning about pools that failed to load
+pub fn load_stakedex(args: &crate::Args, client: &RpcClient) -> Result<Stakedex, Box<dyn Error>> {
+    let accounts = fetch_account_map(client, &Stakedex::init_accounts())?;
+    let (mut stakedex, errs) = Stakedex::from_fetched_accounts(&accounts);
+    for e in errs {
+        args.output
+            .log(format!("[WARN] Failed to initialize pool: {e}"));
+    }
+    let accounts = fetch_account_map(client, &stakedex.get_accounts_to_update())?;
+    for e in stakedex.update(&accounts) {
+        args.output
+            .log(format!("[WARN] Failed to update pool: {e}"));
+    }
+    Ok(stakedex)
+}
+
+fn fetch_account_map(
+    client: &RpcClient,
+    pubkeys: &[Pubkey],
+) -> Result<HashMap<Pubkey, Account>, Box<dyn Error>> {
+    Ok(pubkeys
+        .iter()
+        .zip(get_multiple_accounts_chunked(client, pubkeys)?)
+        .filter_map(|(pubkey, opt)| opt.map(|acc| (*pubkey, acc)))
+        .collect())
+}
+
+/// Lamports of a stake account that are not staked but reserved for rent
+pub fn stake_account_rent() -> u64 {
+    Rent::default().minimum_balance(StakeState::size_of())
+}
+
+/// Quotes swapping `amount` of `mint_in` to `mint_out` through the route a swap takes:
+/// StakeWrappedSol if `mint_in` is wSOL, else SwapViaStake
+pub fn quote_swap(
+    stakedex: &Stakedex,
+    mint_in: &Pubkey,
+    mint_out: &Pubkey,
+    amount: u64,
+) -> (Route, Result<RouteQuote, String>) {
+    let quote_params = QuoteParams {
+        in_amount: amount,
+        input_mint: *mint_in,
+        output_mint: *mint_out,
+        swap_mode: SwapMode::ExactIn,
+    };
+    let (route, res) = match *mint_in == spl_token::native_mint::ID {
+        true => (
+            Route::StakeWrappedSol,
+            stakedex.quote_stake_wrapped_sol(&quote_params),
+        ),
+        false => (
+            Route::SwapViaStake,
+            stakedex.quote_swap_via_stake(&quote_params),
+        ),
+    };
+    let res = res
+        .map(|q| RouteQuote {
+            route,
+            in_amount: q.in_amount,
+            out_amount: q.out_amount,
+            fee_amount: q.fee_amount,
+            fee_mint: q.fee_mint,
+        })
+        .map_err(|e| e.to_string());
+    (route, res)
+}
+
+/// Quotes swapping `amount` of `mint_in` to `mint_out` through every [`Route`], in their declared order.
+/// Routes that dont apply are an error with the reason:
+/// - StakeWrappedSol only if `mint_in` is wSOL, SwapViaStake only if it isnt
+/// - DepositStake only if `mint_in` is wSOL and `voter` is provided, for a stake account
+///   of `amount` lamports delegated to it, so that its output is comparable to StakeWrappedSol's
+pub fn quote_routes(
+    stakedex: &Stakedex,
+    mint_in: &Pubkey,
+    mint_out: &Pubkey,
+    amount: u64,
+    voter: Option<&Pubkey>,
+) -> Vec<(Route, Result<RouteQuote, String>)> {
+    let is_sol_in = *mint_in == spl_token::native_mint::ID;
+    let swap = quote_swap(stakedex, mint_in, mint_out, amount);
+    let (stake_wrapped_sol, swap_via_stake) = match is_sol_in {
+        true => (
+            swap,
+            (Route::SwapViaStake, Err("mint_in is wSOL".to_owned())),
+        ),
+        false => (
+            (
+                Route::StakeWrappedSol,
+                Err("mint_in is not wSOL".to_owned()),
+            ),
+            swap,
+        ),
+    };
+    let deposit_stake = match (is_sol_in, voter) {
+        (false, _) => {
+            Err("only quoted for wSOL in, as a stake account of that many lamports".into())
+        }
+        (true, None) => Err("no vote account to quote a stake account delegated to".into()),
+        (true, Some(voter)) => {
+            let stake = WithdrawStakeQuote {
+                lamports_out: amount,
+                lamports_staked: amount.saturating_sub(stake_account_rent()),
+                voter: *voter,
+                ..Default::default()
+            };
+            quote_deposit_stake(stakedex, mint_out, stake).map(|(_, quote)| RouteQuote {
+                route: Route::DepositStake,
+                in_amount: amount,
+                out_amount: quote.tokens_out,
+                fee_amount: quote.fee_amount,
+                fee_mint: *mint_out,
+            })
+        }
+    };
+    vec![
+        stake_wrapped_sol,
+        swap_via_stake,
+        (Route::DepositStake, deposit_stake),
+    ]
+}
+
+/// Quotes depositing an active stake account into `mint_out`'s pool,
+/// returning the pool to build the DepositStake instruction with
+pub fn quote_deposit_stake<'a>(
+    stakedex: &'a Stakedex,
+    mint_out: &Pubkey,
+    stake: WithdrawStakeQuote,
+) -> Result<(&'a dyn DepositStake, DepositStakeQuote), String> {
+    let pool = stakedex
+        .get_deposit_stake_pool(mint_out)
+        .ok_or_else(|| format!("no pool accepts stake deposits for {mint_out}"))?;
+    if !pool.can_accept_stake_deposits() {
+        return Err(format!("pool of {mint_out} does not accept stake deposits"));
+    }
+    let voter = stake.voter;
+    let quote = pool
+        .get_deposit_stake_quote(stake)
+        .ok_or_else(|| format!("pool of {mint_out} does not accept stake delegated to {voter}"))?;
+    Ok((pool, quote))
+}
+
+/// DepositStake instruction depositing `stake_account` into `mint_out`'s pool
+/// and sending the tokens to `destination`.
+/// `user` must be both the stake and withdraw authority of `stake_account`.
+pub fn deposit_stake_instruction(
+    pool: &dyn DepositStake,
+    quote: &DepositStakeQuote,
+    user: &Pubkey,
+    stake_account: &Pubkey,
+    mint_out: &Pubkey,
+    destination: &Pubkey,
+) -> Result<Instruction, Box<dyn Error>> {
+    let mut ix = deposit_stake_ix(
+        DepositStakeKeys {
+            user: *user,
+            stake_account: *stake_account,
+            dest_token_to: *destination,
+            dest_token_fee_token_account: find_fee_token_acc(mint_out).0,
+            dest_token_mint: *mint_out,
+        },
+        DepositStakeIxArgs {},
+    )?;
+    // the pool's deposit accounts follow stakedex's as remaining accounts
+    let virtual_ix = pool.virtual_ix(
+        quote,
+        &DepositStakeInfo {
+            addr: *stake_account,
+        },
+    )?;
+    ix.accounts.extend(virtual_ix.accounts);
+    Ok(ix)
+}
+
+/// The quote with the highest output
+pub fn best_quote<'a>(
+    quotes: impl IntoIterator<Item = &'a Result<RouteQuote, String>>,
+) -> Option<&'a RouteQuote> {
+    quotes
+        .into_iter()
+        .filter_map(|q| q.as_ref().ok())
+        .max_by_key(|q| q.out_amount)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quote(route: Route, out_amount: u64) -> Result<RouteQuote, String> {
+        Ok(RouteQuote {
+            route,
+            in_amount: 100,
+            out_amount,
+            fee_amount: 1,
+            fee_mint: Pubkey::new_unique(),
+        })
+    }
+
+    #[test]
+    fn test_best_quote() {
+        let quotes = [
+            quote(Route::SwapViaStake, 90),
+            Err("unsupported".to_owned()),
+            quote(Route::DepositStake, 95),
+        ];
+        assert_eq!(best_quote(&quotes).unwrap().route, Route::DepositStake);
+        assert!(best_quote(&quotes[1..2]).is_none());
+    }
+
+    #[test]
+    fn test_quote_routes_xsol_in() {
+        let (stakedex, _) = Stakedex::from_fetched_accounts(&HashMap::new());
+        let quotes = quote_routes(
+            &stakedex,
+            &stakedex_sdk_common::jitosol::ID,
+            &stakedex_sdk_common::msol::ID,
+            1_000_000_000,
+            Some(&Pubkey::new_unique()),
+        );
+        let routes: Vec<_> = quotes.iter().map(|(route, _)| *route).collect();
+        assert_eq!(
+            routes,
+            [
+                Route::StakeWrappedSol,
+                Route::SwapViaStake,
+                Route::DepositStake
+            ]
+        );
+        assert!(quotes[0].1.is_err());
+        // xSOL atomics are not lamports of a stake account, so DepositStake isnt comparable
+        assert!(quotes[2].1.as_ref().unwrap_err().contains("wSOL"));
+    }
+
+    #[test]
+    fn test_quote_routes_sol_in() {
+        let (stakedex, _) = Stakedex::from_fetched_accounts(&HashMap::new());
+        let quotes = quote_routes(
+            &stakedex,
+            &spl_token::native_mint::ID,
+            &stakedex_sdk_common::msol::ID,
+            1_000_000_000,
+            None,
+        );
+        assert_eq!(quotes[1].0, Route::SwapViaStake);
+        assert!(quotes[1].1.is_err());
+        assert!(quotes[2].1.as_ref().unwrap_err().contains("vote account"));
+    }
+
+    #[test]
+    fn test_stake_account_rent() {
+        assert_eq!(stake_account_rent(), 2_282_880);
+    }
+}
@@ -0,0 +1,273 @@
+use std::error::Error;
+
+use clap::{Args, Subcommand};
+use serde::Serialize;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+    instruction::Instruction,
+    pubkey,
+    pubkey::Pubkey,
+};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use stakedex_interface::{
+    close_fee_token_account_ix, withdraw_fees_ix, CloseFeeTokenAccountIxArgs,
+    CloseFeeTokenAccountKeys, WithdrawFeesIxArgs, WithdrawFeesKeys,
+};
+use stakedex_sdk_common::find_fee_token_acc;
+
+use crate::{
+    gov_utils::{encode_instruction_data, propose, to_instruction_data, ProposalArgs},
+    output::{print_output, CmdOutput},
+    token_utils::token_program_of,
+};
+
+use super::SubcmdExec;
+
+/// The admin authority at the time of release, used if it cannot be fetched from chain.
+/// TODO: import from stakedex crate when onchain program is made public
+pub const ADMIN_AUTHORITY: Pubkey = pubkey!("A7jn1BA6LPHX8Wcmc8t476gjoLCG4PZakww19ZXfFRjX");
+
+#[derive(Args, Debug)]
+#[command(
+    long_about = "Outputs a base64-encoded spl-governance program instruction for any stakedex admin instruction, to be executed by the admin authority spl-governance PDA. With --propose, also creates a proposal executing it."
+)]
+pub struct AdminArgs {
+    #[command(subcommand)]
+    pub ix: AdminIx,
+
+    #[command(flatten)]
+    pub proposal: ProposalArgs,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum AdminIx {
+    #[command(about = "Withdraw all fees of a mint from its fee token account")]
+    WithdrawFees {
+        #[arg(help = "Mint of the token to withdraw fees for.")]
+        mint: Pubkey,
+        #[arg(
+            help = "Destination token account to withdraw fees to. Defaults to the admin authority's associated token account if not provided."
+        )]
+        destination: Option<Pubkey>,
+    },
+
+    #[command(about = "Close the fee token account of a mint, removing the mint from stakedex")]
+    CloseFeeTokenAccount {
+        #[arg(help = "Mint of the fee token account to close.")]
+        mint: Pubkey,
+        #[arg(
+            help = "Account to refund the fee token account's rent lamports to. Defaults to the admin authority if not provided."
+        )]
+        close_to: Option<Pubkey>,
+    },
+}
+
+impl AdminIx {
+    pub fn mint(&self) -> &Pubkey {
+        match self {
+            Self::WithdrawFees { mint, .. } => mint,
+            Self::CloseFeeTokenAccount { mint, .. } => mint,
+        }
+    }
+
+    /// `token_program` is the token program that owns the instruction's mint
+    pub fn instruction(&self, admin: &Pubkey, token_program: &Pubkey) -> Instruction {
+        match self {
+            Self::WithdrawFees { mint, destination } => {
+                let destination = destination.unwrap_or_else(|| {
+                    get_associated_token_address_with_program_id(admin, mint, token_program)
+                });
+                withdraw_fees_instruction(admin, mint, &destination, token_program)
+            }
+            Self::CloseFeeTokenAccount { mint, close_to } => close_fee_token_account_ix(
+                CloseFeeTokenAccountKeys {
+                    admin: *admin,
+                    fee_token_account: find_fee_token_acc(mint).0,
+                    close_to: close_to.unwrap_or(*admin),
+                    mint: *mint,
+                    token_program: *token_program,
+                },
+                CloseFeeTokenAccountIxArgs {},
+            )
+            .unwrap(),
+        }
+    }
+
+    /// Summary used as the default proposal name
+    pub fn summary(&self) -> String {
+        match self {
+            Self::WithdrawFees { mint, .. } => format!("Withdraw stakedex fees for {}", mint),
+            Self::CloseFeeTokenAccount { mint, .. } => {
+                format!("Close stakedex fee token account for {}", mint)
+            }
+        }
+    }
+}
+
+impl SubcmdExec for AdminArgs {
+    fn process_cmd(&self, args: &crate::Args) {
+        let admin = resolve_admin(args);
+        let token_program = token_program_of(&args.config.rpc_client(), self.ix.mint()).unwrap();
+        let ix_data = to_instruction_data(self.ix.instruction(&admin, &token_program));
+        let summary = self.ix.summary();
+        let mut output = AdminOutput {
+            summary: summary.clone(),
+            admin: admin.to_string(),
+            instruction: encode_instruction_data(&ix_data),
+            proposal: None,
+        };
+
+        if self.proposal.propose {
+            let proposal = propose(args, &self.proposal, &admin, &summary, vec![vec![ix_data]]);
+            output.proposal = Some(proposal.to_string());
+        }
+        print_output(args.output, &output);
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct AdminOutput {
+    pub summary: String,
+    pub admin: String,
+
+    /// base64-encoded spl-governance InstructionData
+    pub instruction: String,
+
+    /// Some if --propose
+    pub proposal: Option<String>,
+}
+
+impl CmdOutput for AdminOutput {
+    type Row = Self;
+
+    fn text(&self) -> String {
+        let mut lines = vec![format!(
+            "Instruction to {}:\n{}",
+            self.summary, self.instruction
+        )];
+        if let Some(proposal) = &self.proposal {
+            lines.push(format!("Proposal: {}", proposal));
+        }
+        lines.join("\n")
+    }
+
+    fn csv_rows(&self) -> Vec<&Self> {
+        vec![self]
+    }
+}
+
+/// Resolves the admin authority:
+/// - `--admin` if provided
+/// - else the stakedex program's upgrade authority, the governance PDA that administers it
+/// - else ADMIN_AUTHORITY if the upgrade authority cannot be fetched
+///
+/// Warns if the resolved admin differs from ADMIN_AUTHORITY
+pub fn resolve_admin(args: &crate::Args) -> Pubkey {
+    let admin = match args.admin {
+        Some(admin) => admin,
+        None => {
+            let client = args.config.rpc_client();
+            match fetch_upgrade_authority(&client, &stakedex_interface::ID) {
+                Ok(Some(admin)) => admin,
+                Ok(None) => {
+                    args.output.log(format!(
+                        "[WARN] stakedex program is immutable, using compiled admin authority {}",
+                        ADMIN_AUTHORITY
+                    ));
+                    ADMIN_AUTHORITY
+                }
+                Err(e) => {
+                    args.output.log(format!(
+                        "[WARN] Failed to fetch admin authority: {}. Using compiled admin authority {}",
+                        e, ADMIN_AUTHORITY
+                    ));
+                    ADMIN_AUTHORITY
+                }
+            }
+        }
+    };
+    if admin != ADMIN_AUTHORITY {
+        args.output.log(format!(
+            "[WARN] Admin authority {} differs from compiled admin authority {}",
+            admin, ADMIN_AUTHORITY
+        ));
+    }
+    admin
+}
+
+/// Returns None if the program is immutable
+pub fn fetch_upgrade_authority(
+    client: &RpcClient,
+    program_id: &Pubkey,
+) -> Result<Option<Pubkey>, Box<dyn Error>> {
+    let programdata_address =
+        Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::ID).0;
+    let data = client.get_account_data(&programdata_address)?;
+    upgrade_authority_of(&data)
+}
+
+/// Upgrade authority of a programdata account's data. None if the program is immutable
+pub fn upgrade_authority_of(programdata: &[u8]) -> Result<Option<Pubkey>, Box<dyn Error>> {
+    let metadata = programdata
+        .get(..UpgradeableLoaderState::size_of_programdata_metadata())
+        .ok_or("programdata account too small")?;
+    match bincode::deserialize(metadata)? {
+        UpgradeableLoaderState::ProgramData {
+            upgrade_authority_address,
+            ..
+        } => Ok(upgrade_authority_address),
+        _ => Err("not a programdata account".into()),
+    }
+}
+
+pub fn withdraw_fees_instruction(
+    admin: &Pubkey,
+    mint: &Pubkey,
+    destination: &Pubkey,
+    token_program: &Pubkey,
+) -> Instruction {
+    withdraw_fees_ix(
+        WithdrawFeesKeys {
+            admin: *admin,
+            mint: *mint,
+            fee_token_account: find_fee_token_acc(mint).0,
+            withdraw_to: *destination,
+            token_program: *token_program,
+        },
+        WithdrawFeesIxArgs {},
+    )
+    .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn programdata(upgrade_authority_address: Option<Pubkey>) -> Vec<u8> {
+        let mut data = bincode::serialize(&UpgradeableLoaderState::ProgramData {
+            slot: 1,
+            upgrade_authority_address,
+        })
+        .unwrap();
+        data.resize(UpgradeableLoaderState::size_of_programdata_metadata(), 0);
+        // program bytes
+        data.extend([1; 8]);
+        data
+    }
+
+    #[test]
+    fn test_upgrade_authority_of() {
+        assert_eq!(
+            upgrade_authority_of(&programdata(Some(ADMIN_AUTHORITY))).unwrap(),
+            Some(ADMIN_AUTHORITY)
+        );
+        assert_eq!(upgrade_authority_of(&programdata(None)).unwrap(), None);
+        assert!(upgrade_authority_of(&[3, 0, 0, 0]).is_err());
+        let program = bincode::serialize(&UpgradeableLoaderState::Program {
+            programdata_address: Pubkey::new_unique(),
+        })
+        .unwrap();
+        assert!(upgrade_authority_of(&program).is_err());
+    }
+}
@@ -1,14 +1,20 @@
+use std::process;
+
 use clap::Args;
-use solana_sdk::{
-    compute_budget::ComputeBudgetInstruction, message::Message, pubkey::Pubkey, system_program,
-    transaction::Transaction,
-};
+use itertools::izip;
+use serde::Serialize;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey, system_program};
 use stakedex_interface::{
     create_fee_token_account_ix, CreateFeeTokenAccountIxArgs, CreateFeeTokenAccountKeys,
 };
 use stakedex_sdk_common::find_fee_token_acc;
 
-use crate::tx_utils::send_or_sim_tx;
+use crate::{
+    output::{print_output, CmdOutput},
+    token_utils::{get_multiple_accounts_chunked, token_programs_of},
+    tx_utils::{build_tx, pack_instructions, send_or_sim_tx, TxSigners},
+};
 
 use super::SubcmdExec;
 
@@ -17,43 +23,233 @@ use super::SubcmdExec;
     long_about = "Permissionlessly create the fee token account for a xSOL mint. Required to include it into stakedex."
 )]
 pub struct CreateFeeAccArgs {
-    #[arg(help = "Pubkey of the xSOL mint")]
-    pub mint: Pubkey,
+    #[arg(
+        help = "Pubkey of the xSOL mint",
+        required_unless_present = "all_missing"
+    )]
+    pub mint: Option<Pubkey>,
+
+    #[arg(
+        long,
+        help = "Create the fee token accounts of all xSOL mints in the mint registry, or of --mints if provided, that dont exist yet",
+        default_value_t = false,
+        conflicts_with = "mint"
+    )]
+    pub all_missing: bool,
+
+    #[arg(
+        long,
+        help = "Comma-separated mints to create missing fee token accounts for instead of all xSOL mints",
+        value_delimiter = ',',
+        requires = "all_missing"
+    )]
+    pub mints: Vec<Pubkey>,
+
+    #[arg(
+        long,
+        help = "Token program of the mint. Defaults to the mint account's owner, or with --sign-only to the mint registry's token program of the mint",
+        conflicts_with = "all_missing"
+    )]
+    pub token_program: Option<Pubkey>,
 }
 
 impl SubcmdExec for CreateFeeAccArgs {
     fn process_cmd(&self, args: &crate::Args) {
         let client = args.config.rpc_client();
-        let payer = args.config.signer();
-
-        let fee_token_account = find_fee_token_acc(&self.mint).0;
-        let ix = create_fee_token_account_ix(
-            CreateFeeTokenAccountKeys {
-                payer: payer.pubkey(),
-                fee_token_account,
-                mint: self.mint,
-                token_program: spl_token::ID,
-                system_program: system_program::ID,
-            },
-            CreateFeeTokenAccountIxArgs {},
-        )
-        .unwrap();
-        let msg = Message::new(
-            &[
-                // TODO: make compute budget dynamic
-                ComputeBudgetInstruction::set_compute_unit_limit(50_000),
-                ComputeBudgetInstruction::set_compute_unit_price(250),
-                ix,
-            ],
-            Some(&payer.pubkey()),
-        );
-        let blockhash = client.get_latest_blockhash().unwrap();
-        let tx = Transaction::new(&vec![payer], msg, blockhash);
-
-        println!(
-            "Creating fee token acc {} of mint {}",
-            fee_token_account, self.mint
-        );
-        send_or_sim_tx(args, &client, &tx);
+        let payer = args.config.signer(&args.offline);
+        let payer_pk = payer.pubkey();
+
+        if self.all_missing && args.offline.sign_only {
+            args.output.log(
+                "[FAIL] --all-missing needs to fetch which fee token accs exist and cannot be used with --sign-only",
+            );
+            process::exit(1);
+        }
+        let fee_accounts = match self.all_missing {
+            true => self.fee_accs_of_mints(args),
+            false => {
+                let mint = self.mint.unwrap();
+                vec![FeeAccCreation::new(&mint, false)]
+            }
+        };
+        let mut output = CreateFeeAccOutput {
+            fee_accounts,
+            signatures: Vec::new(),
+        };
+        let mints: Vec<Pubkey> = output
+            .fee_accounts
+            .iter()
+            .filter(|f| !f.already_exists)
+            .map(|f| f.mint.parse().unwrap())
+            .collect();
+        if mints.is_empty() {
+            print_output(args.output, &output);
+            return;
+        }
+
+        let token_programs = self.token_programs(args, &client, &mints);
+        let ixs = mints
+            .iter()
+            .zip(token_programs)
+            .map(|(mint, token_program)| {
+                let fee_token_account = find_fee_token_acc(mint).0;
+                args.output.log(format!(
+                    "Creating fee token acc {} of mint {}",
+                    fee_token_account, mint
+                ));
+                create_fee_acc_ix(&payer_pk, mint, &token_program)
+            })
+            .collect();
+        let signers = TxSigners::new(args, payer);
+        for ixs in pack_instructions(ixs, &payer_pk) {
+            let tx = build_tx(args, &client, &ixs, &signers);
+            if let Some(signature) = send_or_sim_tx(args, &client, &tx, &signers) {
+                output.signatures.push(signature.to_string());
+            }
+        }
+        print_output(args.output, &output);
+    }
+}
+
+impl CreateFeeAccArgs {
+    /// Token program of each of `mints`: --token-program if provided,
+    /// else the mint registry's if signing offline, else the mint account's owner
+    fn token_programs(
+        &self,
+        args: &crate::Args,
+        client: &RpcClient,
+        mints: &[Pubkey],
+    ) -> Vec<Pubkey> {
+        if let Some(token_program) = self.token_program {
+            return vec![token_program; mints.len()];
+        }
+        if !args.offline.sign_only {
+            return token_programs_of(client, mints).unwrap();
+        }
+        let registry = args.mint_registry();
+        mints
+            .iter()
+            .map(|mint| match registry.get(mint) {
+                Some(info) => info.token_program,
+                None => {
+                    args.output.log(format!(
+                        "[FAIL] Mint {} is not in the mint registry, provide its token program with --token-program when using --sign-only",
+                        mint
+                    ));
+                    process::exit(1);
+                }
+            })
+            .collect()
+    }
+
+    /// Fee token accounts of `--mints` or the mint registry's mints and whether they already exist
+    fn fee_accs_of_mints(&self, args: &crate::Args) -> Vec<FeeAccCreation> {
+        let client = args.config.rpc_client();
+        let mints = match self.mints.is_empty() {
+            true => args.mint_registry().mints(),
+            false => self.mints.clone(),
+        };
+        let addrs: Vec<_> = mints
+            .iter()
+            .map(|mint| find_fee_token_acc(mint).0)
+            .collect();
+        let fetched = get_multiple_accounts_chunked(&client, &addrs).unwrap();
+        izip!(mints, addrs, fetched)
+            .map(|(mint, addr, opt)| {
+                if opt.is_some() {
+                    args.output.log(format!(
+                        "Fee token acc {} of mint {} already exists",
+                        addr, mint
+                    ));
+                }
+                FeeAccCreation::new(&mint, opt.is_some())
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct FeeAccCreation {
+    pub mint: String,
+
+    pub address: String,
+
+    /// true if the fee token account existed before and was not created
+    pub already_exists: bool,
+}
+
+impl FeeAccCreation {
+    pub fn new(mint: &Pubkey, already_exists: bool) -> Self {
+        Self {
+            mint: mint.to_string(),
+            address: find_fee_token_acc(mint).0.to_string(),
+            already_exists,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct CreateFeeAccOutput {
+    pub fee_accounts: Vec<FeeAccCreation>,
+
+    pub signatures: Vec<String>,
+}
+
+impl CmdOutput for CreateFeeAccOutput {
+    type Row = FeeAccCreation;
+
+    fn text(&self) -> String {
+        match self.fee_accounts.iter().all(|f| f.already_exists) {
+            true => "All fee token accs already exist".to_owned(),
+            false => String::new(),
+        }
+    }
+
+    fn csv_rows(&self) -> Vec<&FeeAccCreation> {
+        self.fee_accounts.iter().collect()
+    }
+}
+
+fn create_fee_acc_ix(payer: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Instruction {
+    create_fee_token_account_ix(
+        CreateFeeTokenAccountKeys {
+            payer: *payer,
+            fee_token_account: find_fee_token_acc(mint).0,
+            mint: *mint,
+            token_program: *token_program,
+            system_program: system_program::ID,
+        },
+        CreateFeeTokenAccountIxArgs {},
+    )
+    .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_fee_acc_ix() {
+        let payer = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let fee_token_account = find_fee_token_acc(&mint).0;
+        let ix = create_fee_acc_ix(&payer, &mint, &spl_token_2022::ID);
+        let meta_of = |pubkey: &Pubkey| ix.accounts.iter().find(|a| a.pubkey == *pubkey).unwrap();
+        assert!(meta_of(&payer).is_signer && meta_of(&payer).is_writable);
+        assert!(meta_of(&fee_token_account).is_writable);
+        // the fee token account is a PDA, created by the program signing for it
+        assert!(!meta_of(&fee_token_account).is_signer);
+        assert!(!meta_of(&mint).is_writable);
+        meta_of(&spl_token_2022::ID);
+        meta_of(&system_program::ID);
+        assert_eq!(ix.accounts.len(), 5);
+    }
+
+    #[test]
+    fn test_fee_acc_creation_address() {
+        let mint = Pubkey::new_unique();
+        let creation = FeeAccCreation::new(&mint, false);
+        assert_eq!(creation.mint, mint.to_string());
+        assert_eq!(creation.address, find_fee_token_acc(&mint).0.to_string());
     }
 }
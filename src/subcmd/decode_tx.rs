@@ -0,0 +1,417 @@
+use std::{error::Error, fs, path::Path, process, str::FromStr, time::Duration};
+
+use clap::Args;
+use serde::Serialize;
+use solana_client::rpc_config::RpcTransactionConfig;
+use solana_program::message::{AccountKeys, SanitizedMessage};
+use solana_sdk::{commitment_config::CommitmentConfig, signature::Signature};
+use solana_transaction_status::{
+    option_serializer::OptionSerializer, EncodedConfirmedTransactionWithStatusMeta, UiInstruction,
+    UiTransactionEncoding,
+};
+use tokio::runtime::Runtime;
+
+use super::{
+    index::{
+        db::schema::Invocation,
+        layout::{ix_args, ix_name, IxLayout},
+        parse::{inner_instructions_of, parse_archived_b64_tx, parse_b64_tx, token_balance_of},
+    },
+    SubcmdExec,
+};
+
+#[derive(Args, Debug)]
+#[command(
+    long_about = "Decode and print all stakedex instructions in a single transaction, including CPIs."
+)]
+pub struct DecodeTxArgs {
+    #[arg(
+        help = "Signature of the transaction to fetch, or path to a JSON file of a base64-encoded getTransaction result"
+    )]
+    pub tx: String,
+
+    #[arg(long, help = "Output as JSON", default_value_t = false)]
+    pub json: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DecodedTx {
+    pub sig: String,
+    pub slot: u64,
+    pub instructions: Vec<DecodedIx>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DecodedIx {
+    pub top_level_index: u8,
+    /// None if top-level
+    pub inner_index: Option<usize>,
+    /// program that CPI'd stakedex, None if top-level
+    pub cpi_prog: Option<String>,
+    pub ix: Option<u8>,
+    pub name: String,
+    /// Debug format of the instruction's `*IxArgs`, None if they failed to decode
+    pub args: Option<String>,
+    pub accounts: Vec<DecodedAccount>,
+    /// None if instruction is not a swap instruction or failed to decode
+    pub invocation: Option<Invocation>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DecodedAccount {
+    pub role: String,
+    pub pubkey: String,
+    pub lamports_delta: Option<i128>,
+    pub token_delta: Option<i128>,
+}
+
+impl SubcmdExec for DecodeTxArgs {
+    fn process_cmd(&self, args: &crate::Args) {
+        let rpc = args.config.nonblocking_rpc_client();
+        let rt = Runtime::new().unwrap();
+        let decoded = rt.block_on(async {
+            let (signature, ectx, smsg) = match Signature::from_str(&self.tx) {
+                Ok(signature) => {
+                    let ectx = rpc
+                        .get_transaction_with_config(
+                            &signature,
+                            RpcTransactionConfig {
+                                encoding: Some(UiTransactionEncoding::Base64),
+                                commitment: Some(CommitmentConfig::confirmed()),
+                                max_supported_transaction_version: Some(0),
+                            },
+                        )
+                        .await
+                        .unwrap();
+                    let (ectx, smsg) = parse_b64_tx(&rpc, ectx).await.unwrap();
+                    (signature, ectx, smsg)
+                }
+                Err(_) if Path::new(&self.tx).is_file() => {
+                    let json = fs::read_to_string(&self.tx).unwrap();
+                    parse_tx_json(&json).unwrap()
+                }
+                Err(e) => {
+                    args.output.log(format!(
+                        "[FAIL] {} is neither a valid transaction signature ({}) nor an existing file",
+                        self.tx, e
+                    ));
+                    process::exit(1);
+                }
+            };
+            decode_tx(&signature, &ectx, &smsg).unwrap()
+        });
+        rt.shutdown_timeout(Duration::from_secs(5));
+
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&decoded).unwrap());
+        } else {
+            print_decoded_tx(&decoded);
+        }
+    }
+}
+
+/// Parses a base64-encoded getTransaction result saved as JSON, returning its first signature
+fn parse_tx_json(
+    json: &str,
+) -> Result<
+    (
+        Signature,
+        EncodedConfirmedTransactionWithStatusMeta,
+        SanitizedMessage,
+    ),
+    Box<dyn Error>,
+> {
+    let ectx: EncodedConfirmedTransactionWithStatusMeta = serde_json::from_str(json)?;
+    let (ectx, smsg) = parse_archived_b64_tx(ectx)?;
+    let signature = *ectx
+        .transaction
+        .transaction
+        .decode()
+        .ok_or("transaction could not be decoded")?
+        .signatures
+        .first()
+        .ok_or("transaction has no signatures")?;
+    Ok((signature, ectx, smsg))
+}
+
+fn decode_tx(
+    signature: &Signature,
+    ectx: &EncodedConfirmedTransactionWithStatusMeta,
+    smsg: &SanitizedMessage,
+) -> Result<DecodedTx, Box<dyn Error>> {
+    let layout = IxLayout::for_slot(ectx.slot)?;
+    let account_keys = smsg.account_keys();
+    let mut instructions = Vec::new();
+
+    let top_level_ixs = smsg.decompile_instructions();
+    for (top_level_index, (compiled, borrowed)) in smsg
+        .instructions()
+        .iter()
+        .zip(top_level_ixs.iter())
+        .enumerate()
+    {
+        if *borrowed.program_id != stakedex_interface::ID {
+            continue;
+        }
+        let (invocation, error) =
+            match Invocation::try_decode_top_level_ix(borrowed, signature, ectx, smsg) {
+                Ok(opt) => (opt, None),
+                Err(e) => (None, Some(e.to_string())),
+            };
+        instructions.push(decoded_ix(
+            ectx,
+            &account_keys,
+            layout,
+            top_level_index.try_into()?,
+            None,
+            None,
+            &compiled.accounts,
+            &compiled.data,
+            invocation,
+            error,
+        ));
+    }
+
+    for top_level_ix in inner_instructions_of(ectx).unwrap_or_default() {
+        let top_level_index = top_level_ix.index;
+        let cpi_prog = smsg
+            .instructions()
+            .get(usize::from(top_level_index))
+            .and_then(|ix| account_keys.get(ix.program_id_index.into()))
+            .map(|pk| pk.to_string());
+        for (inner_index, inner_ix) in top_level_ix.instructions.iter().enumerate() {
+            let compiled = match inner_ix {
+                UiInstruction::Compiled(ix) => ix,
+                UiInstruction::Parsed(..) => continue,
+            };
+            let program_id = account_keys.get(compiled.program_id_index.into());
+            if program_id != Some(&stakedex_interface::ID) {
+                continue;
+            }
+            let (invocation, error) = match Invocation::try_decode_inner_ix(
+                inner_ix,
+                top_level_index,
+                signature,
+                ectx,
+                smsg,
+            ) {
+                Ok(opt) => (opt, None),
+                Err(e) => (None, Some(e.to_string())),
+            };
+            let data = bs58::decode(&compiled.data).into_vec()?;
+            instructions.push(decoded_ix(
+                ectx,
+                &account_keys,
+                layout,
+                top_level_index,
+                Some(inner_index),
+                cpi_prog.clone(),
+                &compiled.accounts,
+                &data,
+                invocation,
+                error,
+            ));
+        }
+    }
+
+    Ok(DecodedTx {
+        sig: signature.to_string(),
+        slot: ectx.slot,
+        instructions,
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+fn decoded_ix(
+    ectx: &EncodedConfirmedTransactionWithStatusMeta,
+    account_keys: &AccountKeys,
+    layout: IxLayout,
+    top_level_index: u8,
+    inner_index: Option<usize>,
+    cpi_prog: Option<String>,
+    account_indexes: &[u8],
+    data: &[u8],
+    invocation: Option<Invocation>,
+    error: Option<String>,
+) -> DecodedIx {
+    let ix = data.first().copied();
+    let args = ix.and_then(|ix| ix_args(ix, &data[1..]));
+    let meta = ectx.transaction.meta.as_ref();
+    let accounts = account_indexes
+        .iter()
+        .enumerate()
+        .map(|(i, account_index)| {
+            let index = usize::from(*account_index);
+            let lamports_delta = meta.and_then(|m| {
+                let pre = m.pre_balances.get(index)?;
+                let post = m.post_balances.get(index)?;
+                Some(i128::from(*post) - i128::from(*pre))
+            });
+            let token_delta = meta.and_then(|m| {
+                let (pre, post) = match (&m.pre_token_balances, &m.post_token_balances) {
+                    (OptionSerializer::Some(pre), OptionSerializer::Some(post)) => (pre, post),
+                    _ => return None,
+                };
+                let pre = token_balance_of(pre, *account_index);
+                let post = token_balance_of(post, *account_index);
+                if pre.is_none() && post.is_none() {
+                    return None;
+                }
+                Some(i128::from(post.unwrap_or(0)) - i128::from(pre.unwrap_or(0)))
+            });
+            DecodedAccount {
+                role: ix.map_or_else(|| format!("accounts[{i}]"), |ix| layout.account_role(ix, i)),
+                pubkey: account_keys
+                    .get(index)
+                    .map_or_else(|| "".into(), |pk| pk.to_string()),
+                lamports_delta,
+                token_delta,
+            }
+        })
+        .collect();
+    DecodedIx {
+        top_level_index,
+        inner_index,
+        cpi_prog,
+        ix,
+        name: ix.map_or("Unknown", ix_name).into(),
+        args,
+        accounts,
+        invocation,
+        error,
+    }
+}
+
+fn print_decoded_tx(decoded: &DecodedTx) {
+    println!("Transaction {} (slot {})", decoded.sig, decoded.slot);
+    if decoded.instructions.is_empty() {
+        println!("No stakedex instructions");
+    }
+    for ix in decoded.instructions.iter() {
+        let position = match ix.inner_index {
+            Some(inner_index) => format!("#{}.{}", ix.top_level_index, inner_index),
+            None => format!("#{}", ix.top_level_index),
+        };
+        match &ix.cpi_prog {
+            Some(cpi_prog) => println!("\n{position} {} (CPI from {cpi_prog})", ix.name),
+            None => println!("\n{position} {}", ix.name),
+        }
+        if let Some(args) = &ix.args {
+            println!("  Args: {args}");
+        }
+        println!("  Accounts:");
+        for acc in ix.accounts.iter() {
+            let mut deltas = String::new();
+            if let Some(d) = acc.lamports_delta.filter(|d| *d != 0) {
+                deltas.push_str(&format!(" | lamports {d:+}"));
+            }
+            if let Some(d) = acc.token_delta.filter(|d| *d != 0) {
+                deltas.push_str(&format!(" | tokens {d:+}"));
+            }
+            println!("    {:<30} {}{}", acc.role, acc.pubkey, deltas);
+        }
+        if let Some(inv) = &ix.invocation {
+            println!(
+                "  Amounts: {} {} -> {} {}",
+                inv.amount_in, inv.mint_in, inv.amount_out, inv.mint_out
+            );
+        }
+        if let Some(e) = &ix.error {
+            println!("  Decode error: {e}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use stakedex_sdk_common::{bsol, cogentsol, jsol};
+
+    use super::*;
+
+    fn decode_example(json: &str) -> DecodedTx {
+        let (signature, ectx, smsg) = parse_tx_json(json).unwrap();
+        decode_tx(&signature, &ectx, &smsg).unwrap()
+    }
+
+    fn account<'a>(ix: &'a DecodedIx, role: &str) -> &'a DecodedAccount {
+        ix.accounts
+            .iter()
+            .find(|acc| acc.role == role)
+            .unwrap_or_else(|| panic!("no {role} account"))
+    }
+
+    #[test]
+    fn test_decode_swap_via_stake() {
+        let decoded = decode_example(include_str!("index/examples/swap_via_stake.json"));
+        assert_eq!(decoded.slot, 206389300);
+        assert_eq!(decoded.instructions.len(), 1);
+        let ix = &decoded.instructions[0];
+        assert_eq!(ix.name, "SwapViaStake");
+        assert_eq!(ix.inner_index, None);
+        assert!(ix.args.as_ref().unwrap().contains("amount: 1500000000"));
+        assert_eq!(ix.accounts[0].role, "user");
+        assert_eq!(
+            account(ix, "src_token_from").token_delta,
+            Some(-1_500_000_000)
+        );
+        assert_eq!(
+            account(ix, "dest_token_to").token_delta,
+            Some(1_436_050_745)
+        );
+        assert_eq!(account(ix, "src_token_mint").pubkey, bsol::ID.to_string());
+        assert_eq!(account(ix, "dest_token_mint").pubkey, jsol::ID.to_string());
+        assert_eq!(ix.accounts[7].role, "remaining[0]");
+        assert!(ix.error.is_none());
+        assert_eq!(ix.invocation.as_ref().unwrap().amount_out, 1_436_050_745);
+    }
+
+    #[test]
+    fn test_decode_stake_wrapped_sol() {
+        let decoded = decode_example(include_str!("index/examples/stake_wrapped_sol.json"));
+        let ix = &decoded.instructions[0];
+        assert_eq!(ix.name, "StakeWrappedSol");
+        assert!(ix.args.as_ref().unwrap().contains("amount: 2000000000"));
+        assert_eq!(
+            account(ix, "dest_token_to").token_delta,
+            Some(1_869_636_257)
+        );
+        assert_eq!(account(ix, "dest_token_mint").pubkey, bsol::ID.to_string());
+        assert_eq!(
+            account(ix, "wsol_mint").pubkey,
+            spl_token::native_mint::ID.to_string()
+        );
+    }
+
+    #[test]
+    fn test_decode_cpi_ixs() {
+        let jup = "JUP5cHjnnCx2DppVsufsLrXs8EBZeEZzGtEK9Gdz6ow";
+        let decoded = decode_example(include_str!("index/examples/cpi_swap_via_stake.json"));
+        let ix = decoded
+            .instructions
+            .iter()
+            .find(|ix| ix.name == "SwapViaStake")
+            .unwrap();
+        assert!(ix.inner_index.is_some());
+        assert_eq!(ix.cpi_prog.as_deref(), Some(jup));
+        assert_eq!(account(ix, "src_token_mint").pubkey, jsol::ID.to_string());
+        assert_eq!(account(ix, "dest_token_mint").pubkey, bsol::ID.to_string());
+
+        let decoded = decode_example(include_str!("index/examples/cpi_stake_wrapped_sol.json"));
+        let ix = decoded
+            .instructions
+            .iter()
+            .find(|ix| ix.name == "StakeWrappedSol")
+            .unwrap();
+        assert_eq!(ix.cpi_prog.as_deref(), Some(jup));
+        assert_eq!(
+            account(ix, "dest_token_mint").pubkey,
+            cogentsol::ID.to_string()
+        );
+    }
+
+    #[test]
+    fn test_parse_tx_json_invalid() {
+        assert!(parse_tx_json("not a tx").is_err());
+    }
+}
@@ -0,0 +1,381 @@
+use std::process;
+
+use clap::Args;
+use serde::Serialize;
+use solana_sdk::{
+    clock::Clock,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    stake::{
+        self,
+        state::{Authorized, Stake, StakeAuthorize, StakeState},
+    },
+    sysvar,
+};
+use spl_associated_token_account::{
+    get_associated_token_address_with_program_id,
+    instruction::create_associated_token_account_idempotent,
+};
+use spl_token::amount_to_ui_amount;
+use stakedex_sdk_common::WithdrawStakeQuote;
+
+use crate::{
+    output::{print_output, CmdOutput},
+    stakedex_utils::{deposit_stake_instruction, load_stakedex, quote_deposit_stake},
+    token_utils::{mint_decimals_of, token_program_of},
+    tx_utils::{build_tx, send_or_sim_tx, TxSigners},
+};
+
+use super::SubcmdExec;
+
+#[derive(Args, Debug)]
+#[command(
+    long_about = "Sell an active stake account for xSOL through stakedex's DepositStake instruction. Authorities other than the fee payer sign over both authorities to the fee payer in the same transaction, since DepositStake requires it to hold both"
+)]
+pub struct DepositStakeArgs {
+    #[arg(help = "Pubkey of the stake account to deposit")]
+    pub stake_account: Pubkey,
+
+    #[arg(help = "Pubkey of the xSOL mint to receive")]
+    pub mint_out: Pubkey,
+
+    #[arg(
+        long,
+        help = "Stake authority keypair path, or pubkey if signing offline. Defaults to the fee payer"
+    )]
+    pub stake_authority: Option<String>,
+
+    #[arg(
+        long,
+        help = "Withdraw authority keypair path, or pubkey if signing offline. Defaults to the fee payer"
+    )]
+    pub withdraw_authority: Option<String>,
+}
+
+impl SubcmdExec for DepositStakeArgs {
+    fn process_cmd(&self, args: &crate::Args) {
+        let client = args.config.rpc_client();
+        let payer = args.config.signer(&args.offline);
+        let user = payer.pubkey();
+        let mut signers = TxSigners::new(args, payer);
+        let [stake_authority, withdraw_authority] =
+            [&self.stake_authority, &self.withdraw_authority]
+                .map(|path| path.as_deref().map(|p| args.offline.signer_from_path(p)));
+        let authorized = Authorized {
+            staker: stake_authority.as_ref().map_or(user, |s| s.pubkey()),
+            withdrawer: withdraw_authority.as_ref().map_or(user, |s| s.pubkey()),
+        };
+
+        let fetched = client
+            .get_multiple_accounts(&[self.stake_account, sysvar::clock::ID, self.mint_out])
+            .unwrap();
+        let stake_acc = fetched[0]
+            .as_ref()
+            .unwrap_or_else(|| panic!("Stake account {} not found", self.stake_account));
+        let clock: Clock = bincode::deserialize(&fetched[1].as_ref().unwrap().data).unwrap();
+        let decimals = mint_decimals_of(fetched[2].as_ref())
+            .unwrap_or_else(|| panic!("Could not fetch decimals of mint {}", self.mint_out));
+        let stake = match stake_acc.owner == stake::program::ID {
+            true => bincode::deserialize(&stake_acc.data)
+                .map_err(|e| e.to_string())
+                .and_then(|state| check_stake_account(&state, &authorized, &clock)),
+            false => Err("not owned by the stake program".into()),
+        }
+        .unwrap_or_else(|e| {
+            args.output.log(format!(
+                "[FAIL] Stake account {}: {}",
+                self.stake_account, e
+            ));
+            process::exit(1);
+        });
+        let voter = stake.delegation.voter_pubkey;
+
+        let stakedex = load_stakedex(args, &client).unwrap();
+        let withdraw_quote = WithdrawStakeQuote {
+            lamports_out: stake_acc.lamports,
+            lamports_staked: stake.delegation.stake,
+            voter,
+            ..Default::default()
+        };
+        let (pool, quote) = quote_deposit_stake(&stakedex, &self.mint_out, withdraw_quote)
+            .unwrap_or_else(|e| {
+                args.output
+                    .log(format!("[FAIL] DepositStake unavailable: {}", e));
+                process::exit(1);
+            });
+
+        let token_program = token_program_of(&client, &self.mint_out).unwrap();
+        let destination =
+            get_associated_token_address_with_program_id(&user, &self.mint_out, &token_program);
+        let mut output = DepositStakeOutput {
+            stake_account: self.stake_account.to_string(),
+            voter: voter.to_string(),
+            lamports: stake_acc.lamports,
+            staked_lamports: stake.delegation.stake,
+            mint_out: self.mint_out.to_string(),
+            out_amount: quote.tokens_out,
+            out_balance: amount_to_ui_amount(quote.tokens_out, decimals),
+            fee_amount: quote.fee_amount,
+            destination: destination.to_string(),
+            signature: None,
+        };
+        args.output.log(output.quote_text());
+
+        let mut ixs = authorize_ixs(&self.stake_account, &authorized, &user);
+        ixs.extend([
+            create_associated_token_account_idempotent(
+                &user,
+                &user,
+                &self.mint_out,
+                &token_program,
+            ),
+            deposit_stake_instruction(
+                pool,
+                &quote,
+                &user,
+                &self.stake_account,
+                &self.mint_out,
+                &destination,
+            )
+            .unwrap(),
+        ]);
+        for authority in [stake_authority, withdraw_authority].into_iter().flatten() {
+            signers.add_signer(authority);
+        }
+        let tx = build_tx(args, &client, &ixs, &signers);
+        output.signature = send_or_sim_tx(args, &client, &tx, &signers).map(|s| s.to_string());
+        print_output(args.output, &output);
+    }
+}
+
+/// Checks that a stake account can be deposited with `authorized` signing:
+/// - delegated and fully activated, not deactivating
+/// - `authorized` are its stake and withdraw authorities
+/// - not locked up, so that the withdraw authority can be reassigned without the custodian
+fn check_stake_account(
+    state: &StakeState,
+    authorized: &Authorized,
+    clock: &Clock,
+) -> Result<Stake, String> {
+    let (meta, stake) = match state {
+        StakeState::Stake(meta, stake) => (meta, stake),
+        _ => return Err("not delegated".into()),
+    };
+    let delegation = &stake.delegation;
+    if delegation.deactivation_epoch != u64::MAX {
+        return Err(format!(
+            "deactivating or deactivated since epoch {}",
+            delegation.deactivation_epoch
+        ));
+    }
+    if delegation.activation_epoch >= clock.epoch {
+        return Err(format!(
+            "not yet active, activated in epoch {}",
+            delegation.activation_epoch
+        ));
+    }
+    if meta.authorized.staker != authorized.staker {
+        return Err(format!(
+            "stake authority is {}, not {}. Provide it with --stake-authority",
+            meta.authorized.staker, authorized.staker
+        ));
+    }
+    if meta.authorized.withdrawer != authorized.withdrawer {
+        return Err(format!(
+            "withdraw authority is {}, not {}. Provide it with --withdraw-authority",
+            meta.authorized.withdrawer, authorized.withdrawer
+        ));
+    }
+    if meta.lockup.is_in_force(clock, None) {
+        return Err(format!(
+            "locked up until epoch {} / unix timestamp {}",
+            meta.lockup.epoch, meta.lockup.unix_timestamp
+        ));
+    }
+    Ok(*stake)
+}
+
+/// Instructions reassigning the stake and withdraw authorities of `stake_account` from `authorized`
+/// to `user`, who DepositStake requires to hold both. Empty if `user` already does
+fn authorize_ixs(
+    stake_account: &Pubkey,
+    authorized: &Authorized,
+    user: &Pubkey,
+) -> Vec<Instruction> {
+    [
+        (&authorized.staker, StakeAuthorize::Staker),
+        (&authorized.withdrawer, StakeAuthorize::Withdrawer),
+    ]
+    .into_iter()
+    .filter(|(authority, _)| *authority != user)
+    .map(|(authority, role)| {
+        stake::instruction::authorize(stake_account, authority, user, role, None)
+    })
+    .collect()
+}
+
+#[derive(Debug, Serialize)]
+pub struct DepositStakeOutput {
+    pub stake_account: String,
+    pub voter: String,
+
+    /// Total lamports of the stake account
+    pub lamports: u64,
+
+    /// Delegated lamports of the stake account
+    pub staked_lamports: u64,
+
+    pub mint_out: String,
+
+    /// Quoted xSOL output, in atomics
+    pub out_amount: u64,
+
+    /// Quoted xSOL output, in tokens
+    pub out_balance: f64,
+
+    /// Quoted fee charged in mint_out, in atomics
+    pub fee_amount: u64,
+
+    /// Token account of mint_out that receives the output
+    pub destination: String,
+
+    pub signature: Option<String>,
+}
+
+impl DepositStakeOutput {
+    fn quote_text(&self) -> String {
+        format!(
+            "Depositing stake account {} ({} lamports, {} staked to {}) for {} of {} (fee: {} atomics) into {}",
+            self.stake_account,
+            self.lamports,
+            self.staked_lamports,
+            self.voter,
+            self.out_balance,
+            self.mint_out,
+            self.fee_amount,
+            self.destination
+        )
+    }
+}
+
+impl CmdOutput for DepositStakeOutput {
+    type Row = Self;
+
+    fn text(&self) -> String {
+        match &self.signature {
+            Some(signature) => format!(
+                "Deposited stake account {}: {}",
+                self.stake_account, signature
+            ),
+            None => String::new(),
+        }
+    }
+
+    fn csv_rows(&self) -> Vec<&Self> {
+        vec![self]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::stake::state::{Delegation, Lockup, Meta};
+
+    use super::*;
+
+    fn stake_state(user: &Pubkey, activation_epoch: u64, lockup_epoch: u64) -> StakeState {
+        StakeState::Stake(
+            Meta {
+                rent_exempt_reserve: 2_282_880,
+                authorized: Authorized {
+                    staker: *user,
+                    withdrawer: *user,
+                },
+                lockup: Lockup {
+                    epoch: lockup_epoch,
+                    ..Default::default()
+                },
+            },
+            Stake {
+                delegation: Delegation {
+                    voter_pubkey: Pubkey::new_unique(),
+                    stake: 1_000_000_000,
+                    activation_epoch,
+                    ..Default::default()
+                },
+                credits_observed: 0,
+            },
+        )
+    }
+
+    fn authorized(staker: &Pubkey, withdrawer: &Pubkey) -> Authorized {
+        Authorized {
+            staker: *staker,
+            withdrawer: *withdrawer,
+        }
+    }
+
+    #[test]
+    fn test_check_stake_account() {
+        let user = Pubkey::new_unique();
+        let both = authorized(&user, &user);
+        let clock = Clock {
+            epoch: 10,
+            ..Default::default()
+        };
+        assert!(check_stake_account(&stake_state(&user, 9, 0), &both, &clock).is_ok());
+        // activating
+        assert!(check_stake_account(&stake_state(&user, 10, 0), &both, &clock).is_err());
+        // locked up
+        assert!(check_stake_account(&stake_state(&user, 9, 11), &both, &clock).is_err());
+        // wrong authorities
+        let other = Pubkey::new_unique();
+        for wrong in [authorized(&other, &user), authorized(&user, &other)] {
+            assert!(check_stake_account(&stake_state(&user, 9, 0), &wrong, &clock).is_err());
+        }
+        // deactivating
+        let mut state = stake_state(&user, 9, 0);
+        if let StakeState::Stake(_, stake) = &mut state {
+            stake.delegation.deactivation_epoch = 10;
+        }
+        assert!(check_stake_account(&state, &both, &clock).is_err());
+        assert!(check_stake_account(&StakeState::Uninitialized, &both, &clock).is_err());
+    }
+
+    #[test]
+    fn test_authorize_ixs() {
+        let stake_account = Pubkey::new_unique();
+        let user = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        assert!(authorize_ixs(&stake_account, &authorized(&user, &user), &user).is_empty());
+
+        let ixs = authorize_ixs(&stake_account, &authorized(&authority, &user), &user);
+        assert_eq!(
+            ixs,
+            [stake::instruction::authorize(
+                &stake_account,
+                &authority,
+                &user,
+                StakeAuthorize::Staker,
+                None
+            )]
+        );
+
+        // staker first, so that the withdraw authority isnt needed to reassign it
+        let ixs = authorize_ixs(&stake_account, &authorized(&authority, &authority), &user);
+        assert_eq!(ixs.len(), 2);
+        assert_eq!(
+            ixs[1],
+            stake::instruction::authorize(
+                &stake_account,
+                &authority,
+                &user,
+                StakeAuthorize::Withdrawer,
+                None
+            )
+        );
+        assert!(ixs.iter().all(|ix| ix
+            .accounts
+            .iter()
+            .any(|a| a.pubkey == authority && a.is_signer)));
+    }
+}
@@ -0,0 +1,487 @@
+use std::{fmt::Display, process};
+
+use clap::Args;
+use itertools::izip;
+use serde::Serialize;
+use solana_address_lookup_table_program::state::AddressLookupTable;
+use solana_sdk::{
+    account::Account, bpf_loader_upgradeable, clock::Slot, program_pack::Pack, pubkey,
+    pubkey::Pubkey, rent::Rent, system_program, sysvar,
+};
+use stakedex_sdk_common::{find_fee_token_acc, find_sol_bridge_out};
+
+use crate::{
+    mint_registry::MintInfo,
+    output::{print_output, CmdOutput},
+    token_utils::{
+        get_multiple_accounts_chunked, is_token_program, unpack_mint, unpack_token_account,
+    },
+};
+
+use super::{upgrade_authority_of, SubcmdExec, ADMIN_AUTHORITY};
+
+/// Address lookup table of stakedex's accounts at the time of release
+pub const STAKEDEX_LUT: Pubkey = pubkey!("51aCqmnbfSuiBt2mF1jvKY5J1AypyLCVNMqa1fiGtZM3");
+
+#[derive(Args, Debug)]
+#[command(
+    long_about = "Check all accounts the stakedex program relies on: the program and its admin authority, sol_bridge_out, address lookup tables, and the mint and fee token account of every xSOL mint in the mint registry. Exits with a non-zero code if any check fails."
+)]
+pub struct DoctorArgs {
+    #[arg(
+        long,
+        help = "Comma-separated address lookup tables to check",
+        value_delimiter = ',',
+        default_values_t = [STAKEDEX_LUT]
+    )]
+    pub luts: Vec<Pubkey>,
+}
+
+impl SubcmdExec for DoctorArgs {
+    fn process_cmd(&self, args: &crate::Args) {
+        let client = args.config.rpc_client();
+        let registry = args.mint_registry();
+        let program_id = stakedex_interface::ID;
+        let programdata =
+            Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::ID).0;
+        let sol_bridge_out = find_sol_bridge_out().0;
+        let fee_accs: Vec<_> = registry
+            .mints
+            .iter()
+            .map(|info| find_fee_token_acc(&info.mint).0)
+            .collect();
+
+        // everything in one batch
+        let mut fetched = get_multiple_accounts_chunked(
+            &client,
+            &[
+                vec![program_id, programdata, sol_bridge_out, sysvar::rent::ID],
+                self.luts.clone(),
+                registry.mints(),
+                fee_accs.clone(),
+            ]
+            .concat(),
+        )
+        .unwrap();
+        let fetched_fee_accs = fetched.split_off(fetched.len() - fee_accs.len());
+        let fetched_mints = fetched.split_off(fetched.len() - registry.mints.len());
+        let fetched_luts = fetched.split_off(4);
+        let rent: Rent = bincode::deserialize(&fetched[3].as_ref().unwrap().data).unwrap();
+        let expected_admin = args.admin.unwrap_or(ADMIN_AUTHORITY);
+
+        let mut checks = vec![
+            check_program(&program_id, fetched[0].as_ref()),
+            check_admin(&programdata, fetched[1].as_ref(), &expected_admin),
+            check_sol_bridge_out(&sol_bridge_out, fetched[2].as_ref(), &rent),
+        ];
+        for (lut, acc) in self.luts.iter().zip(fetched_luts.iter()) {
+            checks.push(check_lut(lut, acc.as_ref()));
+        }
+        for (info, acc) in registry.mints.iter().zip(fetched_mints.iter()) {
+            checks.push(check_mint(info, acc.as_ref()));
+        }
+
+        for (info, addr, acc, mint_acc) in izip!(
+            &registry.mints,
+            &fee_accs,
+            &fetched_fee_accs,
+            &fetched_mints
+        ) {
+            checks.push(check_fee_acc(
+                info,
+                addr,
+                acc.as_ref(),
+                mint_acc.as_ref().map(|a| &a.owner),
+                &rent,
+            ));
+        }
+
+        let output = DoctorOutput { checks };
+        print_output(args.output, &output);
+        if output.checks.iter().any(|c| c.status == CheckStatus::Fail) {
+            process::exit(1);
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Ok,
+    Warn,
+    Fail,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Check {
+    pub name: String,
+
+    pub address: String,
+
+    pub status: CheckStatus,
+
+    pub message: String,
+}
+
+impl Check {
+    fn new(
+        name: impl Into<String>,
+        address: &Pubkey,
+        status: CheckStatus,
+        message: impl Display,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            address: address.to_string(),
+            status,
+            message: message.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct DoctorOutput {
+    pub checks: Vec<Check>,
+}
+
+impl CmdOutput for DoctorOutput {
+    type Row = Check;
+
+    fn text(&self) -> String {
+        let mut lines: Vec<_> = self
+            .checks
+            .iter()
+            .map(|c| {
+                let status = match c.status {
+                    CheckStatus::Ok => "[OK]",
+                    CheckStatus::Warn => "[WARN]",
+                    CheckStatus::Fail => "[FAIL]",
+                };
+                format!("{} {} {}: {}", status, c.name, c.address, c.message)
+            })
+            .collect();
+        let count = |status| self.checks.iter().filter(|c| c.status == status).count();
+        lines.push(format!(
+            "{} checks: {} ok, {} warnings, {} failures",
+            self.checks.len(),
+            count(CheckStatus::Ok),
+            count(CheckStatus::Warn),
+            count(CheckStatus::Fail)
+        ));
+        lines.join("\n")
+    }
+
+    fn csv_rows(&self) -> Vec<&Check> {
+        self.checks.iter().collect()
+    }
+}
+
+fn check_program(program_id: &Pubkey, acc: Option<&Account>) -> Check {
+    let (status, message) = match acc {
+        None => (CheckStatus::Fail, "does not exist".to_owned()),
+        Some(a) if !a.executable => (CheckStatus::Fail, "not executable".to_owned()),
+        Some(a) if a.owner != bpf_loader_upgradeable::ID => (
+            CheckStatus::Warn,
+            format!("owned by {}, not the upgradeable loader", a.owner),
+        ),
+        Some(_) => (CheckStatus::Ok, "deployed".to_owned()),
+    };
+    Check::new("program", program_id, status, message)
+}
+
+fn check_admin(programdata: &Pubkey, acc: Option<&Account>, expected_admin: &Pubkey) -> Check {
+    let (status, message) = match acc.map(|a| upgrade_authority_of(&a.data)) {
+        None => (
+            CheckStatus::Fail,
+            "programdata account does not exist".to_owned(),
+        ),
+        Some(Err(e)) => (CheckStatus::Fail, format!("invalid programdata: {e}")),
+        Some(Ok(None)) => (
+            CheckStatus::Warn,
+            "program is immutable, admin instructions use the compiled admin authority".to_owned(),
+        ),
+        Some(Ok(Some(admin))) if admin != *expected_admin => (
+            CheckStatus::Warn,
+            format!(
+                "upgrade authority {admin} differs from expected admin authority {expected_admin}"
+            ),
+        ),
+        Some(Ok(Some(admin))) => (CheckStatus::Ok, format!("upgrade authority {admin}")),
+    };
+    Check::new("admin authority", programdata, status, message)
+}
+
+/// sol_bridge_out must hold enough lamports to be rent-exempt as a token account
+/// for StakeWrappedSol to work
+fn check_sol_bridge_out(addr: &Pubkey, acc: Option<&Account>, rent: &Rent) -> Check {
+    let required = rent.minimum_balance(spl_token::state::Account::LEN);
+    let (status, message) = match acc {
+        None => (
+            CheckStatus::Fail,
+            format!("does not exist, fund it with fund-sol-bridge ({required} lamports required)"),
+        ),
+        Some(a) if a.owner != system_program::ID => (
+            CheckStatus::Fail,
+            format!("owned by {}, expected system program", a.owner),
+        ),
+        Some(a) if a.lamports < required => (
+            CheckStatus::Fail,
+            format!(
+                "{} lamports < {required} required, fund it with fund-sol-bridge",
+                a.lamports
+            ),
+        ),
+        Some(a) => (
+            CheckStatus::Ok,
+            format!("{} lamports >= {required} required", a.lamports),
+        ),
+    };
+    Check::new("sol_bridge_out", addr, status, message)
+}
+
+fn check_lut(addr: &Pubkey, acc: Option<&Account>) -> Check {
+    let (status, message) = match acc {
+        None => (CheckStatus::Fail, "does not exist".to_owned()),
+        Some(a) if a.owner != solana_address_lookup_table_program::id() => (
+            CheckStatus::Fail,
+            format!("owned by {}, not the address lookup table program", a.owner),
+        ),
+        Some(a) => match AddressLookupTable::deserialize(&a.data) {
+            Err(e) => (CheckStatus::Fail, format!("invalid lookup table: {e}")),
+            Ok(lut) if lut.meta.deactivation_slot != Slot::MAX => (
+                CheckStatus::Fail,
+                format!("deactivated at slot {}", lut.meta.deactivation_slot),
+            ),
+            Ok(lut) => (
+                CheckStatus::Ok,
+                format!(
+                    "{} addresses, authority {}",
+                    lut.addresses.len(),
+                    lut.meta
+                        .authority
+                        .map_or_else(|| "none (frozen)".to_owned(), |a| a.to_string())
+                ),
+            ),
+        },
+    };
+    Check::new("lookup table", addr, status, message)
+}
+
+fn check_mint(info: &MintInfo, acc: Option<&Account>) -> Check {
+    let (status, message) = match acc {
+        None => (CheckStatus::Fail, "does not exist".to_owned()),
+        Some(a) if !is_token_program(&a.owner) => (
+            CheckStatus::Fail,
+            format!("owned by {}, not a token program", a.owner),
+        ),
+        Some(a) => match unpack_mint(&a.data) {
+            Err(e) => (CheckStatus::Fail, format!("invalid mint account: {e}")),
+            Ok(mint) if mint.decimals != info.decimals => (
+                CheckStatus::Warn,
+                format!(
+                    "registered with {} decimals, mint has {}",
+                    info.decimals, mint.decimals
+                ),
+            ),
+            Ok(mint) => (
+                CheckStatus::Ok,
+                format!("{} decimals, owned by {}", mint.decimals, a.owner),
+            ),
+        },
+    };
+    Check::new(format!("mint {}", info.symbol), &info.mint, status, message)
+}
+
+/// `mint_owner` is the token program owning the fee token account's mint, None if the mint doesnt exist.
+/// The fee token account must be owned by itself, the PDA stakedex signs for it with
+fn check_fee_acc(
+    info: &MintInfo,
+    addr: &Pubkey,
+    acc: Option<&Account>,
+    mint_owner: Option<&Pubkey>,
+    rent: &Rent,
+) -> Check {
+    let name = format!("fee token acc {}", info.symbol);
+    let acc = match acc {
+        Some(a) => a,
+        None => {
+            return Check::new(
+                name,
+                addr,
+                CheckStatus::Fail,
+                "does not exist, create it with create-fee-acc",
+            )
+        }
+    };
+    let (status, message) = match unpack_token_account(&acc.data) {
+        _ if !is_token_program(&acc.owner) => (
+            CheckStatus::Fail,
+            format!("owned by {}, not a token program", acc.owner),
+        ),
+        _ if mint_owner.is_some_and(|owner| *owner != acc.owner) => (
+            CheckStatus::Fail,
+            format!(
+                "owned by {}, but the mint is owned by {}",
+                acc.owner,
+                mint_owner.unwrap()
+            ),
+        ),
+        Err(e) => (CheckStatus::Fail, format!("invalid token account: {e}")),
+        Ok(parsed) if parsed.mint != info.mint => (
+            CheckStatus::Fail,
+            format!(
+                "token account of mint {}, expected {}",
+                parsed.mint, info.mint
+            ),
+        ),
+        _ if !rent.is_exempt(acc.lamports, acc.data.len()) => (
+            CheckStatus::Fail,
+            format!("{} lamports is not rent-exempt", acc.lamports),
+        ),
+        Ok(parsed) if parsed.owner != *addr => (
+            CheckStatus::Fail,
+            format!(
+                "owned by {}, not by itself, so stakedex cannot sign for it",
+                parsed.owner
+            ),
+        ),
+        Ok(parsed) => (
+            CheckStatus::Ok,
+            format!("balance {} atomics", parsed.amount),
+        ),
+    };
+    Check::new(name, addr, status, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use solana_address_lookup_table_program::state::LookupTableMeta;
+    use solana_sdk::program_option::COption;
+    use spl_token::state::{Account as TokenAccount, AccountState};
+
+    use super::*;
+
+    fn account(owner: Pubkey, lamports: u64, data: Vec<u8>) -> Account {
+        Account {
+            lamports,
+            data,
+            owner,
+            executable: false,
+            rent_epoch: 0,
+        }
+    }
+
+    fn token_account(mint: Pubkey, owner: Pubkey, lamports: u64) -> Account {
+        let mut data = vec![0; TokenAccount::LEN];
+        TokenAccount {
+            mint,
+            owner,
+            amount: 5,
+            delegate: COption::None,
+            state: AccountState::Initialized,
+            is_native: COption::None,
+            delegated_amount: 0,
+            close_authority: COption::None,
+        }
+        .pack_into_slice(&mut data);
+        account(spl_token::ID, lamports, data)
+    }
+
+    fn mint_info() -> MintInfo {
+        MintInfo {
+            mint: Pubkey::new_unique(),
+            symbol: "xSOL".into(),
+            decimals: 9,
+            token_program: spl_token::ID,
+            pool_program: None,
+            pool: None,
+        }
+    }
+
+    #[test]
+    fn test_check_sol_bridge_out() {
+        let rent = Rent::default();
+        let addr = Pubkey::new_unique();
+        let required = rent.minimum_balance(TokenAccount::LEN);
+        let status = |acc: Option<Account>| check_sol_bridge_out(&addr, acc.as_ref(), &rent).status;
+        assert_eq!(status(None), CheckStatus::Fail);
+        assert_eq!(
+            status(Some(account(system_program::ID, required - 1, vec![]))),
+            CheckStatus::Fail
+        );
+        assert_eq!(
+            status(Some(account(spl_token::ID, required, vec![]))),
+            CheckStatus::Fail
+        );
+        assert_eq!(
+            status(Some(account(system_program::ID, required, vec![]))),
+            CheckStatus::Ok
+        );
+    }
+
+    #[test]
+    fn test_check_fee_acc() {
+        let rent = Rent::default();
+        let info = mint_info();
+        let addr = find_fee_token_acc(&info.mint).0;
+        let lamports = rent.minimum_balance(TokenAccount::LEN);
+        let status = |acc: Account, mint_owner: &Pubkey| {
+            check_fee_acc(&info, &addr, Some(&acc), Some(mint_owner), &rent).status
+        };
+
+        let valid = token_account(info.mint, addr, lamports);
+        assert_eq!(status(valid.clone(), &spl_token::ID), CheckStatus::Ok);
+        // mint owned by token-2022 but fee acc by spl-token
+        assert_eq!(
+            status(valid.clone(), &spl_token_2022::ID),
+            CheckStatus::Fail
+        );
+        assert_eq!(
+            status(
+                token_account(Pubkey::new_unique(), addr, lamports),
+                &spl_token::ID
+            ),
+            CheckStatus::Fail
+        );
+        assert_eq!(
+            status(token_account(info.mint, addr, lamports - 1), &spl_token::ID),
+            CheckStatus::Fail
+        );
+        assert_eq!(
+            status(
+                token_account(info.mint, Pubkey::new_unique(), lamports),
+                &spl_token::ID
+            ),
+            CheckStatus::Fail
+        );
+        assert_eq!(
+            check_fee_acc(&info, &addr, None, None, &rent).status,
+            CheckStatus::Fail
+        );
+    }
+
+    #[test]
+    fn test_check_lut() {
+        let addr = Pubkey::new_unique();
+        let lut = |deactivation_slot| {
+            let data = AddressLookupTable {
+                meta: LookupTableMeta {
+                    deactivation_slot,
+                    authority: None,
+                    ..LookupTableMeta::default()
+                },
+                addresses: Cow::Owned(vec![Pubkey::new_unique(); 3]),
+            }
+            .serialize_for_tests()
+            .unwrap();
+            account(solana_address_lookup_table_program::id(), 1, data)
+        };
+        let active = check_lut(&addr, Some(&lut(Slot::MAX)));
+        assert_eq!(active.status, CheckStatus::Ok);
+        assert_eq!(active.message, "3 addresses, authority none (frozen)");
+        assert_eq!(check_lut(&addr, Some(&lut(5))).status, CheckStatus::Fail);
+        assert_eq!(check_lut(&addr, None).status, CheckStatus::Fail);
+    }
+}
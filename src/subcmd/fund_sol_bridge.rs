@@ -1,11 +1,13 @@
 use clap::Args;
-use solana_sdk::{
-    message::Message, program_pack::Pack, system_instruction, transaction::Transaction,
-};
+use serde::Serialize;
+use solana_sdk::{program_pack::Pack, rent::Rent, system_instruction};
 use spl_token::state::Account;
 use stakedex_sdk_common::find_sol_bridge_out;
 
-use crate::tx_utils::send_or_sim_tx;
+use crate::{
+    output::{print_output, CmdOutput},
+    tx_utils::{build_tx, send_or_sim_tx, TxSigners},
+};
 
 use super::SubcmdExec;
 
@@ -18,32 +20,104 @@ pub struct FundSolBridgeArgs;
 impl SubcmdExec for FundSolBridgeArgs {
     fn process_cmd(&self, args: &crate::Args) {
         let client = args.config.rpc_client();
-        let payer = args.config.signer();
+        let payer = args.config.signer(&args.offline);
 
         let sol_bridge_out = find_sol_bridge_out().0;
 
-        let existing = client.get_balance(&sol_bridge_out).unwrap();
-        let lamports_req = client
-            .get_minimum_balance_for_rent_exemption(Account::get_packed_len())
-            .unwrap();
-
-        if existing >= lamports_req {
-            println!(
-                "{} already has enough ({} lamports >= {} required)",
-                sol_bridge_out, existing, lamports_req
-            );
+        // no RPC access when signing offline, assume cluster uses default rent
+        // and that the caller checked sol_bridge_out needs funding
+        let (existing, lamports_req) = match args.offline.sign_only {
+            true => (
+                None,
+                Rent::default().minimum_balance(Account::get_packed_len()),
+            ),
+            false => (
+                Some(client.get_balance(&sol_bridge_out).unwrap()),
+                client
+                    .get_minimum_balance_for_rent_exemption(Account::get_packed_len())
+                    .unwrap(),
+            ),
+        };
+        let mut output = FundSolBridgeOutput {
+            sol_bridge_out: sol_bridge_out.to_string(),
+            existing_lamports: existing,
+            required_lamports: lamports_req,
+            transfer_lamports: 0,
+            signature: None,
+        };
+        let transfer_lamports = lamports_to_transfer(existing, lamports_req);
+        if transfer_lamports == 0 {
+            print_output(args.output, &output);
             return;
         }
 
-        let ix = system_instruction::transfer(&payer.pubkey(), &sol_bridge_out, lamports_req);
-        let msg = Message::new(&[ix], Some(&payer.pubkey()));
-        let blockhash = client.get_latest_blockhash().unwrap();
-        let tx = Transaction::new(&vec![payer], msg, blockhash);
+        let ix = system_instruction::transfer(&payer.pubkey(), &sol_bridge_out, transfer_lamports);
+        let signers = TxSigners::new(args, payer);
+        let tx = build_tx(args, &client, &[ix], &signers);
 
-        println!(
+        args.output.log(format!(
             "Transfering {} lamports to {}",
-            lamports_req, sol_bridge_out,
-        );
-        send_or_sim_tx(args, &client, &tx);
+            transfer_lamports, sol_bridge_out,
+        ));
+        output.transfer_lamports = transfer_lamports;
+        output.signature = send_or_sim_tx(args, &client, &tx, &signers).map(|s| s.to_string());
+        print_output(args.output, &output);
+    }
+}
+
+/// Lamports to fund sol_bridge_out with, 0 if it already has `required`.
+/// `existing` is None if unknown, i.e. signing offline
+fn lamports_to_transfer(existing: Option<u64>, required: u64) -> u64 {
+    match existing {
+        Some(existing) if existing >= required => 0,
+        _ => required,
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct FundSolBridgeOutput {
+    pub sol_bridge_out: String,
+
+    /// None if signing offline
+    pub existing_lamports: Option<u64>,
+
+    pub required_lamports: u64,
+
+    /// 0 if sol_bridge_out already has enough lamports
+    pub transfer_lamports: u64,
+
+    /// None if no transfer was required
+    pub signature: Option<String>,
+}
+
+impl CmdOutput for FundSolBridgeOutput {
+    type Row = Self;
+
+    fn text(&self) -> String {
+        match (self.transfer_lamports, self.existing_lamports) {
+            (0, Some(existing)) => format!(
+                "{} already has enough ({} lamports >= {} required)",
+                self.sol_bridge_out, existing, self.required_lamports
+            ),
+            _ => String::new(),
+        }
+    }
+
+    fn csv_rows(&self) -> Vec<&Self> {
+        vec![self]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lamports_to_transfer() {
+        assert_eq!(lamports_to_transfer(Some(2_039_280), 2_039_280), 0);
+        assert_eq!(lamports_to_transfer(Some(3_000_000), 2_039_280), 0);
+        assert_eq!(lamports_to_transfer(Some(1), 2_039_280), 2_039_280);
+        assert_eq!(lamports_to_transfer(Some(0), 2_039_280), 2_039_280);
+        assert_eq!(lamports_to_transfer(None, 2_039_280), 2_039_280);
     }
 }
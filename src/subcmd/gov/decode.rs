@@ -0,0 +1,30 @@
+use std::process;
+
+use base64::{engine::general_purpose, Engine};
+use borsh::BorshDeserialize;
+use clap::Args;
+use spl_governance::state::proposal_transaction::InstructionData;
+
+use crate::subcmd::{resolve_admin, SubcmdExec};
+
+use super::print_and_verify;
+
+#[derive(Args, Debug)]
+#[command(
+    long_about = "Decode a base64-encoded spl-governance InstructionData, such as the output of withdraw-fees, and verify its accounts. Exits with a non-zero code if verification fails."
+)]
+pub struct GovDecodeArgs {
+    #[arg(help = "base64-encoded borsh-serialized InstructionData")]
+    pub ix_data: String,
+}
+
+impl SubcmdExec for GovDecodeArgs {
+    fn process_cmd(&self, args: &crate::Args) {
+        let bytes = general_purpose::STANDARD.decode(&self.ix_data).unwrap();
+        let ix_data = InstructionData::try_from_slice(&bytes).unwrap();
+        let admin = resolve_admin(args);
+        if !print_and_verify(args.output, &ix_data, &admin) {
+            process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use std::process;
+
+use clap::Args;
+use solana_program::borsh::try_from_slice_unchecked;
+use solana_sdk::pubkey::Pubkey;
+use spl_governance::state::{
+    proposal::ProposalV2,
+    proposal_transaction::{get_proposal_transaction_address, ProposalTransactionV2},
+};
+
+use crate::subcmd::{resolve_admin, SubcmdExec};
+
+use super::print_and_verify;
+
+#[derive(Args, Debug)]
+#[command(
+    long_about = "Decode and verify all instructions of an spl-governance proposal. Exits with a non-zero code if verification of any instruction fails."
+)]
+pub struct InspectProposalArgs {
+    #[arg(help = "Address of the proposal")]
+    pub proposal: Pubkey,
+}
+
+impl SubcmdExec for InspectProposalArgs {
+    fn process_cmd(&self, args: &crate::Args) {
+        let client = args.config.rpc_client();
+        let account = client.get_account(&self.proposal).unwrap();
+        // the governance program that owns the proposal
+        let program_id = account.owner;
+        let proposal: ProposalV2 = try_from_slice_unchecked(&account.data).unwrap();
+        args.output
+            .log(format!("Proposal {} \"{}\"", self.proposal, proposal.name));
+        args.output
+            .log(format!("Governance: {}", proposal.governance));
+        args.output.log(format!("State: {:?}", proposal.state));
+
+        let admin = resolve_admin(args);
+        let mut ok = true;
+        for (option_index, option) in proposal.options.iter().enumerate() {
+            let option_index: u8 = option_index.try_into().unwrap();
+            let addrs: Vec<_> = (0..option.transactions_next_index)
+                .map(|index| {
+                    get_proposal_transaction_address(
+                        &program_id,
+                        &self.proposal,
+                        &option_index.to_le_bytes(),
+                        &index.to_le_bytes(),
+                    )
+                })
+                .collect();
+            // chunk to stay under getMultipleAccounts limit
+            for addrs in addrs.chunks(100) {
+                let fetched = client.get_multiple_accounts(addrs).unwrap();
+                for (addr, opt) in addrs.iter().zip(fetched) {
+                    // removed proposal transactions leave gaps in the indices
+                    let acc = match opt {
+                        Some(a) => a,
+                        None => continue,
+                    };
+                    let ptx: ProposalTransactionV2 = try_from_slice_unchecked(&acc.data).unwrap();
+                    args.output.log(format!(
+                        "\nOption {} \"{}\" transaction {} ({})",
+                        option_index, option.label, ptx.transaction_index, addr
+                    ));
+                    for ix_data in ptx.instructions.iter() {
+                        ok &= print_and_verify(args.output, ix_data, &admin);
+                    }
+                }
+            }
+        }
+        if !ok {
+            args.output
+                .log("\n[FAIL] Proposal contains instructions that failed verification");
+            process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,216 @@
+use clap::{Args, Subcommand};
+use solana_sdk::pubkey::Pubkey;
+use spl_governance::state::proposal_transaction::InstructionData;
+use stakedex_sdk_common::find_fee_token_acc;
+
+use crate::{output::OutputFormat, token_utils::is_token_program};
+
+use self::{decode::GovDecodeArgs, inspect_proposal::InspectProposalArgs};
+
+use super::{
+    index::layout::{account_roles, ix_args, ix_name},
+    SubcmdExec,
+};
+
+mod decode;
+mod inspect_proposal;
+
+#[derive(Args, Debug)]
+#[command(
+    long_about = "Decode and verify spl-governance instructions and proposals executing stakedex admin instructions"
+)]
+pub struct GovArgs {
+    #[command(subcommand)]
+    pub subcmd: GovSubcmd,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum GovSubcmd {
+    Decode(GovDecodeArgs),
+    InspectProposal(InspectProposalArgs),
+}
+
+impl SubcmdExec for GovArgs {
+    fn process_cmd(&self, args: &crate::Args) {
+        match &self.subcmd {
+            GovSubcmd::Decode(a) => a.process_cmd(args),
+            GovSubcmd::InspectProposal(a) => a.process_cmd(args),
+        }
+    }
+}
+
+/// Prints the instruction's named accounts and args and verifies that
+/// its admin is `expected_admin` and its fee token account is the mint's PDA.
+///
+/// Returns false if any check failed.
+fn print_and_verify(
+    output: OutputFormat,
+    ix_data: &InstructionData,
+    expected_admin: &Pubkey,
+) -> bool {
+    output.log(format!("Program: {}", ix_data.program_id));
+    if ix_data.program_id != stakedex_interface::ID {
+        output.log("  [WARN] Not a stakedex instruction");
+        for (i, acc) in ix_data.accounts.iter().enumerate() {
+            output.log(format!("    accounts[{i}] {}", acc.pubkey));
+        }
+        output.log(format!(
+            "  Data: {}",
+            bs58::encode(&ix_data.data).into_string()
+        ));
+        return false;
+    }
+
+    let ix = match ix_data.data.first() {
+        Some(ix) => *ix,
+        None => {
+            output.log("  [WARN] Empty instruction data");
+            return false;
+        }
+    };
+    output.log(format!("Instruction: {}", ix_name(ix)));
+    let mut ok = true;
+    match ix_args(ix, &ix_data.data[1..]) {
+        Some(args) => output.log(format!("  Args: {args}")),
+        None => {
+            output.log(format!(
+                "  [FAIL] Args data {} does not deserialize",
+                bs58::encode(&ix_data.data[1..]).into_string()
+            ));
+            ok = false;
+        }
+    }
+    let roles = account_roles(ix);
+    output.log("  Accounts:");
+    for (i, acc) in ix_data.accounts.iter().enumerate() {
+        let role = roles.get(i).map_or_else(
+            || format!("remaining[{}]", i - roles.len()),
+            |r| (*r).into(),
+        );
+        let mut flags = String::new();
+        if acc.is_signer {
+            flags.push_str(" signer");
+        }
+        if acc.is_writable {
+            flags.push_str(" writable");
+        }
+        output.log(format!("    {:<30} {}{}", role, acc.pubkey, flags));
+    }
+
+    let account_of = |role: &str| {
+        roles
+            .iter()
+            .position(|r| *r == role)
+            .and_then(|i| ix_data.accounts.get(i))
+            .map(|acc| acc.pubkey)
+    };
+    if let Some(admin) = account_of("admin") {
+        ok &= check(output, "admin authority", &admin, expected_admin);
+    }
+    if let (Some(mint), Some(fee_token_account)) =
+        (account_of("mint"), account_of("fee_token_account"))
+    {
+        ok &= check(
+            output,
+            "fee token account",
+            &fee_token_account,
+            &find_fee_token_acc(&mint).0,
+        );
+    }
+    if let Some(token_program) = account_of("token_program") {
+        let is_valid = is_token_program(&token_program);
+        match is_valid {
+            true => output.log(format!("  [OK] token program {token_program}")),
+            false => output.log(format!(
+                "  [FAIL] token program {token_program} is not spl-token or token-2022"
+            )),
+        }
+        ok &= is_valid;
+    }
+    ok
+}
+
+fn check(output: OutputFormat, name: &str, actual: &Pubkey, expected: &Pubkey) -> bool {
+    match actual == expected {
+        true => {
+            output.log(format!("  [OK] {name} {actual}"));
+            true
+        }
+        false => {
+            output.log(format!("  [FAIL] {name} {actual}, expected {expected}"));
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use stakedex_interface::{WithdrawFeesIxArgs, WithdrawFeesKeys};
+
+    use crate::{gov_utils::to_instruction_data, subcmd::ADMIN_AUTHORITY};
+
+    use super::*;
+
+    fn withdraw_fees_ix_data(admin: Pubkey, fee_token_account: Pubkey) -> InstructionData {
+        let ix = stakedex_interface::withdraw_fees_ix(
+            WithdrawFeesKeys {
+                admin,
+                mint: spl_token::native_mint::ID,
+                fee_token_account,
+                withdraw_to: Pubkey::new_unique(),
+                token_program: spl_token::ID,
+            },
+            WithdrawFeesIxArgs {},
+        )
+        .unwrap();
+        to_instruction_data(ix)
+    }
+
+    #[test]
+    fn test_verify_withdraw_fees() {
+        let fee_token_account = find_fee_token_acc(&spl_token::native_mint::ID).0;
+        assert!(print_and_verify(
+            OutputFormat::Text,
+            &withdraw_fees_ix_data(ADMIN_AUTHORITY, fee_token_account),
+            &ADMIN_AUTHORITY
+        ));
+        assert!(!print_and_verify(
+            OutputFormat::Text,
+            &withdraw_fees_ix_data(Pubkey::new_unique(), fee_token_account),
+            &ADMIN_AUTHORITY
+        ));
+        assert!(!print_and_verify(
+            OutputFormat::Text,
+            &withdraw_fees_ix_data(ADMIN_AUTHORITY, Pubkey::new_unique()),
+            &ADMIN_AUTHORITY
+        ));
+    }
+
+    #[test]
+    fn test_verify_non_stakedex() {
+        let mut ix_data = withdraw_fees_ix_data(
+            ADMIN_AUTHORITY,
+            find_fee_token_acc(&spl_token::native_mint::ID).0,
+        );
+        ix_data.program_id = Pubkey::new_unique();
+        assert!(!print_and_verify(
+            OutputFormat::Text,
+            &ix_data,
+            &ADMIN_AUTHORITY
+        ));
+    }
+
+    #[test]
+    fn test_verify_malformed_args() {
+        let mut ix_data = withdraw_fees_ix_data(
+            ADMIN_AUTHORITY,
+            find_fee_token_acc(&spl_token::native_mint::ID).0,
+        );
+        ix_data.data = vec![stakedex_interface::STAKE_WRAPPED_SOL_IX_DISCM, 1];
+        assert!(!print_and_verify(
+            OutputFormat::Text,
+            &ix_data,
+            &ADMIN_AUTHORITY
+        ));
+    }
+}
@@ -0,0 +1,161 @@
+use std::{collections::HashMap, error::Error, fs::File, path::Path};
+
+use rusqlite::Connection;
+use solana_sdk::{pubkey, pubkey::Pubkey};
+
+use crate::mint_registry::MintRegistry;
+
+const JUP_V2: Pubkey = pubkey!("JUP2jxvXaqu7NQY1GmNF4m1vodw12LVXYxbFL2uJvfo");
+const JUP_V3: Pubkey = pubkey!("JUP3c2Uh3WA4Ng34tw6kPd2G4C5BB21Xo36Je1s32Ph");
+const JUP_V4: Pubkey = pubkey!("JUP4Fb2cqiRUcaTHdrPC8h2gNsA2ETXiPDD33WcGuJB");
+const JUP_V5: Pubkey = pubkey!("JUP5cHjnnCx2DppVsufsLrXs8EBZeEZzGtEK9Gdz6ow");
+const JUP_V6: Pubkey = pubkey!("JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4");
+
+const PROGRAM_LABELS: &[(Pubkey, &str)] = &[
+    (stakedex_interface::ID, "Stakedex"),
+    (JUP_V2, "Jupiter v2"),
+    (JUP_V3, "Jupiter v3"),
+    (JUP_V4, "Jupiter v4"),
+    (JUP_V5, "Jupiter v5"),
+    (JUP_V6, "Jupiter v6"),
+];
+
+/// Labels bundled with the CLI, lowest precedence:
+/// known programs, then the symbol of every mint in `registry`
+pub fn default_labels(registry: &MintRegistry) -> Vec<(Pubkey, String)> {
+    PROGRAM_LABELS
+        .iter()
+        .map(|(address, label)| (*address, (*label).to_owned()))
+        .chain(
+            registry
+                .mints
+                .iter()
+                .map(|info| (info.mint, info.symbol.clone())),
+        )
+        .collect()
+}
+
+/// Reads a JSON object of `{ "<address>": "<label>" }`,
+/// same shape as the solana CLI config's address_labels
+pub fn load_labels_file<P: AsRef<Path>>(
+    path: P,
+) -> Result<HashMap<String, String>, Box<dyn Error>> {
+    let file = File::open(path)?;
+    Ok(serde_json::from_reader(file)?)
+}
+
+/// Replaces all labels with the default labels of `registry`, followed by each map in `overrides` in order.
+/// Later labels replace earlier ones for the same address.
+/// Labels of a previous run that are no longer provided are removed.
+pub fn seed_labels(
+    conn: &Connection,
+    registry: &MintRegistry,
+    overrides: &[&HashMap<String, String>],
+) -> rusqlite::Result<()> {
+    let tx = conn.unchecked_transaction()?;
+    tx.execute("DELETE FROM labels", [])?;
+    {
+        let mut stmt =
+            tx.prepare_cached("INSERT OR REPLACE INTO labels (address, label) VALUES (?1, ?2)")?;
+        for (address, label) in default_labels(registry) {
+            stmt.execute([address.to_string(), label])?;
+        }
+        for map in overrides {
+            for (address, label) in map.iter() {
+                stmt.execute([address, label])?;
+            }
+        }
+    }
+    tx.commit()
+}
+
+#[cfg(test)]
+mod tests {
+    use stakedex_interface::SWAP_VIA_STAKE_IX_DISCM;
+    use stakedex_sdk_common::{jitosol, msol};
+
+    use crate::subcmd::index::db::{schema::Invocation, test_utils::create_test_db};
+
+    use super::*;
+
+    fn summary_of(conn: &Connection, sig: &str) -> String {
+        conn.query_row(
+            "SELECT summary FROM invocation_summaries WHERE sig = ?1",
+            [sig],
+            |row| row.get(0),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_summary_labels() {
+        let conn = create_test_db();
+        seed_labels(&conn, &MintRegistry::bundled(), &[]).unwrap();
+        Invocation {
+            sig: "abc".into(),
+            signer: "def".into(),
+            ix: SWAP_VIA_STAKE_IX_DISCM,
+            unix_timestamp: 1,
+            slot: 206389300,
+            cpi_prog: JUP_V4.to_string(),
+            amount_in: 1,
+            amount_out: 1,
+            mint_in: jitosol::ID.to_string(),
+            mint_out: msol::ID.to_string(),
+        }
+        .save(&conn)
+        .unwrap();
+        assert_eq!(summary_of(&conn, "abc"), "Jupiter v4: jitoSOL -> mSOL");
+    }
+
+    #[test]
+    fn test_override_labels() {
+        let conn = create_test_db();
+        let unknown_mint = Pubkey::new_unique().to_string();
+        let overrides = HashMap::from([
+            (msol::ID.to_string(), "Marinade SOL".to_owned()),
+            (unknown_mint.clone(), "newSOL".to_owned()),
+        ]);
+        seed_labels(&conn, &MintRegistry::bundled(), &[&overrides]).unwrap();
+        Invocation {
+            sig: "abc".into(),
+            signer: "def".into(),
+            ix: SWAP_VIA_STAKE_IX_DISCM,
+            unix_timestamp: 1,
+            slot: 206389300,
+            cpi_prog: "".into(),
+            amount_in: 1,
+            amount_out: 1,
+            mint_in: unknown_mint,
+            mint_out: msol::ID.to_string(),
+        }
+        .save(&conn)
+        .unwrap();
+        assert_eq!(summary_of(&conn, "abc"), "newSOL -> Marinade SOL");
+    }
+
+    #[test]
+    fn test_reseed_removes_labels() {
+        let conn = create_test_db();
+        let label_of = |address: &str| -> Option<String> {
+            conn.query_row(
+                "SELECT label FROM labels WHERE address = ?1",
+                [address],
+                |row| row.get(0),
+            )
+            .ok()
+        };
+        let removed = Pubkey::new_unique().to_string();
+        let overrides = HashMap::from([
+            (removed.clone(), "oldSOL".to_owned()),
+            (msol::ID.to_string(), "Marinade SOL".to_owned()),
+        ]);
+        seed_labels(&conn, &MintRegistry::bundled(), &[&overrides]).unwrap();
+        assert_eq!(label_of(&removed).as_deref(), Some("oldSOL"));
+
+        seed_labels(&conn, &MintRegistry::bundled(), &[]).unwrap();
+        assert_eq!(label_of(&removed), None);
+        assert_eq!(label_of(&msol::ID.to_string()).as_deref(), Some("mSOL"));
+        assert_eq!(label_of(&JUP_V6.to_string()).as_deref(), Some("Jupiter v6"));
+    }
+}
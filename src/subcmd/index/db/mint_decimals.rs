@@ -0,0 +1,81 @@
+use std::{error::Error, str::FromStr};
+
+use rusqlite::Connection;
+use solana_sdk::pubkey::Pubkey;
+
+/// Distinct mints of all invocations that dont have their decimals saved yet
+pub fn mints_missing_decimals(conn: &Connection) -> Result<Vec<Pubkey>, Box<dyn Error>> {
+    let mut stmt = conn.prepare(
+        "SELECT mint FROM (
+            SELECT mint_in AS mint FROM invocations
+            UNION
+            SELECT mint_out AS mint FROM invocations
+        )
+        WHERE mint != '' AND mint NOT IN (SELECT mint FROM mint_decimals)",
+    )?;
+    let mints = stmt.query_map([], |row| row.get::<_, String>(0))?;
+    let mut res = Vec::new();
+    for mint in mints {
+        res.push(Pubkey::from_str(&mint?)?);
+    }
+    Ok(res)
+}
+
+pub fn save_mint_decimals(conn: &Connection, mint: &Pubkey, decimals: u8) -> rusqlite::Result<()> {
+    let mut stmt = conn.prepare_cached(
+        "INSERT OR REPLACE INTO mint_decimals (mint, decimals, atomics_per_token) VALUES (?1, ?2, ?3)",
+    )?;
+    stmt.execute((mint.to_string(), decimals, 10i64.pow(decimals.into())))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use stakedex_interface::SWAP_VIA_STAKE_IX_DISCM;
+    use stakedex_sdk_common::{jitosol, msol};
+
+    use crate::subcmd::index::db::{schema::Invocation, test_utils::create_test_db};
+
+    use super::*;
+
+    #[test]
+    fn test_invocation_amounts() {
+        let conn = create_test_db();
+        Invocation {
+            sig: "abc".into(),
+            signer: "def".into(),
+            ix: SWAP_VIA_STAKE_IX_DISCM,
+            unix_timestamp: 1,
+            slot: 206389300,
+            cpi_prog: "".into(),
+            amount_in: 1_500_000_000,
+            amount_out: 2_000_000,
+            mint_in: jitosol::ID.to_string(),
+            mint_out: msol::ID.to_string(),
+        }
+        .save(&conn)
+        .unwrap();
+
+        let mut missing = mints_missing_decimals(&conn).unwrap();
+        missing.sort();
+        let mut expected = vec![jitosol::ID, msol::ID];
+        expected.sort();
+        assert_eq!(missing, expected);
+
+        save_mint_decimals(&conn, &jitosol::ID, 9).unwrap();
+        assert_eq!(mints_missing_decimals(&conn).unwrap(), vec![msol::ID]);
+        let amounts = || {
+            conn.query_row(
+                "SELECT ui_amount_in, ui_amount_out FROM invocation_amounts WHERE sig = 'abc'",
+                [],
+                |row| Ok((row.get::<_, f64>(0)?, row.get::<_, Option<f64>>(1)?)),
+            )
+            .unwrap()
+        };
+        assert_eq!(amounts(), (1.5, None));
+
+        save_mint_decimals(&conn, &msol::ID, 6).unwrap();
+        assert!(mints_missing_decimals(&conn).unwrap().is_empty());
+        assert_eq!(amounts(), (1.5, Some(2.0)));
+    }
+}
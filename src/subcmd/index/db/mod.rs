@@ -4,14 +4,30 @@ use rusqlite_migration::{Migrations, M};
 use solana_sdk::signature::Signature;
 use std::{error::Error, include_str, path::Path, str::FromStr};
 
+pub mod labels;
+pub mod mint_decimals;
 pub mod schema;
+pub mod summary;
 pub mod test_utils;
 
 lazy_static! {
     static ref MIGRATION_1_UP: &'static str = include_str!("schema/1_up.sql");
     static ref MIGRATION_1_DOWN: &'static str = include_str!("schema/1_down.sql");
-    static ref MIGRATIONS: Migrations<'static> =
-        Migrations::new(vec![M::up(&MIGRATION_1_UP).down(&MIGRATION_1_DOWN),]);
+    static ref MIGRATION_2_UP: &'static str = include_str!("schema/2_up.sql");
+    static ref MIGRATION_2_DOWN: &'static str = include_str!("schema/2_down.sql");
+    static ref MIGRATION_3_UP: &'static str = include_str!("schema/3_up.sql");
+    static ref MIGRATION_3_DOWN: &'static str = include_str!("schema/3_down.sql");
+    static ref MIGRATION_4_UP: &'static str = include_str!("schema/4_up.sql");
+    static ref MIGRATION_4_DOWN: &'static str = include_str!("schema/4_down.sql");
+    static ref MIGRATION_5_UP: &'static str = include_str!("schema/5_up.sql");
+    static ref MIGRATION_5_DOWN: &'static str = include_str!("schema/5_down.sql");
+    static ref MIGRATIONS: Migrations<'static> = Migrations::new(vec![
+        M::up(&MIGRATION_1_UP).down(&MIGRATION_1_DOWN),
+        M::up(&MIGRATION_2_UP).down(&MIGRATION_2_DOWN),
+        M::up(&MIGRATION_3_UP).down(&MIGRATION_3_DOWN),
+        M::up(&MIGRATION_4_UP).down(&MIGRATION_4_DOWN),
+        M::up(&MIGRATION_5_UP).down(&MIGRATION_5_DOWN),
+    ]);
 }
 
 /// panics if any DB errors encountered
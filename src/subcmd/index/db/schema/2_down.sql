@@ -0,0 +1,3 @@
+DROP VIEW IF EXISTS invocation_summaries;
+DROP VIEW IF EXISTS labelled_invocations;
+DROP TABLE IF EXISTS labels;
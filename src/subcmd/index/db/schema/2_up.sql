@@ -0,0 +1,30 @@
+-- Human-readable labels for addresses (programs, mints, signers).
+-- Seeded from the CLI's bundled registry + solana CLI config address_labels + user labels file on every run.
+CREATE TABLE IF NOT EXISTS labels (
+    address TEXT PRIMARY KEY NOT NULL, -- base58 pubkey
+    label TEXT NOT NULL -- e.g. "Jupiter v4", "jitoSOL"
+);
+
+-- invocations with labels joined in. Falls back to the raw address if no label exists.
+CREATE VIEW IF NOT EXISTS labelled_invocations AS
+SELECT
+    i.*,
+    COALESCE(l_signer.label, i.signer) AS signer_label,
+    COALESCE(l_cpi_prog.label, i.cpi_prog) AS cpi_prog_label,
+    COALESCE(l_mint_in.label, i.mint_in) AS mint_in_label,
+    COALESCE(l_mint_out.label, i.mint_out) AS mint_out_label
+FROM invocations i
+LEFT JOIN labels l_signer ON l_signer.address = i.signer
+LEFT JOIN labels l_cpi_prog ON l_cpi_prog.address = i.cpi_prog
+LEFT JOIN labels l_mint_in ON l_mint_in.address = i.mint_in
+LEFT JOIN labels l_mint_out ON l_mint_out.address = i.mint_out;
+
+-- one-line description of each invocation e.g. "Jupiter v4: jitoSOL -> mSOL"
+CREATE VIEW IF NOT EXISTS invocation_summaries AS
+SELECT
+    sig,
+    slot,
+    unix_timestamp,
+    CASE WHEN cpi_prog = '' THEN '' ELSE cpi_prog_label || ': ' END
+        || mint_in_label || ' -> ' || mint_out_label AS summary
+FROM labelled_invocations;
@@ -0,0 +1,10 @@
+-- stakedex instructions that failed to decode into invocations, to be retried with `index reprocess-errors`
+CREATE TABLE IF NOT EXISTS decode_errors (
+    sig TEXT NOT NULL, -- transaction signature
+    top_level_index INTEGER NOT NULL, -- index of the top-level instruction in the transaction
+    inner_index INTEGER NOT NULL DEFAULT -1, -- index of the inner instruction in the top-level instruction. -1 if stakedex instruction is top-level
+    ix INTEGER NOT NULL DEFAULT -1, -- instruction discriminant. -1 if unable to determine
+    error TEXT NOT NULL, -- decode error message
+    binary_version TEXT NOT NULL, -- version of the CLI that failed to decode the instruction
+    UNIQUE (sig, top_level_index, inner_index)
+);
@@ -0,0 +1,7 @@
+-- Archive of raw transactions as returned by getTransaction, for re-decoding with `index redecode` without RPC.
+-- Only populated if indexing with --archive
+CREATE TABLE IF NOT EXISTS raw_transactions (
+    sig TEXT PRIMARY KEY NOT NULL, -- transaction signature
+    slot INTEGER NOT NULL, -- block/slot number
+    data BLOB NOT NULL -- gzip-compressed JSON of base64-encoded transaction + meta
+);
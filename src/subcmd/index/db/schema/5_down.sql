@@ -0,0 +1,2 @@
+DROP VIEW IF EXISTS invocation_amounts;
+DROP TABLE IF EXISTS mint_decimals;
@@ -0,0 +1,23 @@
+-- Decimals of every mint seen in invocations, for converting token atomics into token amounts.
+-- Seeded from on-chain mint accounts on every run.
+CREATE TABLE IF NOT EXISTS mint_decimals (
+    mint TEXT PRIMARY KEY NOT NULL, -- base58 pubkey
+    decimals INTEGER NOT NULL,
+    atomics_per_token INTEGER NOT NULL -- 10^decimals, since sqlite may not have pow()
+);
+
+-- labelled_invocations with amounts in tokens instead of token atomics.
+-- Amounts are NULL if the mint's decimals are unknown.
+CREATE VIEW IF NOT EXISTS invocation_amounts AS
+SELECT
+    i.sig,
+    i.slot,
+    i.unix_timestamp,
+    i.cpi_prog_label,
+    i.mint_in_label,
+    CAST(i.amount_in AS REAL) / d_in.atomics_per_token AS ui_amount_in,
+    i.mint_out_label,
+    CAST(i.amount_out AS REAL) / d_out.atomics_per_token AS ui_amount_out
+FROM labelled_invocations i
+LEFT JOIN mint_decimals d_in ON d_in.mint = i.mint_in
+LEFT JOIN mint_decimals d_out ON d_out.mint = i.mint_out;
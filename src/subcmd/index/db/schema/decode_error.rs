@@ -0,0 +1,140 @@
+use std::{error::Error, str::FromStr};
+
+use rusqlite::Connection;
+use solana_sdk::signature::Signature;
+
+use crate::subcmd::index::consts::BINARY_VERSION;
+
+/// A stakedex instruction that could not be decoded into an Invocation
+#[derive(Clone, Debug, PartialEq)]
+pub struct DecodeError {
+    pub sig: String,
+    pub top_level_index: u8,
+    /// None if the stakedex instruction is top-level, else index into the top-level instruction's inner instructions
+    pub inner_index: Option<usize>,
+    /// None if instruction data could not be read
+    pub ix: Option<u8>,
+    pub error: String,
+    /// version of the CLI that failed to decode the instruction
+    pub binary_version: String,
+}
+
+impl DecodeError {
+    pub fn new(
+        signature: &Signature,
+        top_level_index: u8,
+        inner_index: Option<usize>,
+        ix: Option<u8>,
+        error: &dyn Error,
+    ) -> Self {
+        Self {
+            sig: signature.to_string(),
+            top_level_index,
+            inner_index,
+            ix,
+            error: error.to_string(),
+            binary_version: BINARY_VERSION.into(),
+        }
+    }
+
+    pub fn save(&self, conn: &Connection) -> rusqlite::Result<()> {
+        let mut stmt = conn.prepare_cached(
+            "INSERT OR REPLACE INTO decode_errors
+            (sig, top_level_index, inner_index, ix, error, binary_version)
+            VALUES
+            (:sig, :top_level_index, :inner_index, :ix, :error, :binary_version)",
+        )?;
+        stmt.execute(&[
+            (":sig", &self.sig),
+            (":top_level_index", &self.top_level_index.to_string()),
+            (
+                ":inner_index",
+                &self
+                    .inner_index
+                    .map_or_else(|| "-1".to_owned(), |i| i.to_string()),
+            ),
+            (
+                ":ix",
+                &self.ix.map_or_else(|| "-1".to_owned(), |i| i.to_string()),
+            ),
+            (":error", &self.error),
+            (":binary_version", &self.binary_version),
+        ])?;
+        Ok(())
+    }
+}
+
+/// Signatures of all transactions with at least one decode error
+pub fn signatures_with_decode_errors(conn: &Connection) -> Result<Vec<Signature>, Box<dyn Error>> {
+    let mut stmt = conn.prepare("SELECT DISTINCT sig FROM decode_errors ORDER BY sig")?;
+    let sigs = stmt.query_map([], |row| row.get::<_, String>(0))?;
+    let mut res = Vec::new();
+    for sig in sigs {
+        res.push(Signature::from_str(&sig?)?);
+    }
+    Ok(res)
+}
+
+/// Deletes all invocations and decode errors for a transaction
+/// so that it can be re-decoded without creating duplicate rows
+pub fn clear_signature(conn: &Connection, signature: &Signature) -> rusqlite::Result<()> {
+    let sig = signature.to_string();
+    conn.execute("DELETE FROM invocations WHERE sig = ?1", [&sig])?;
+    conn.execute("DELETE FROM decode_errors WHERE sig = ?1", [&sig])?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::subcmd::index::db::{schema::Invocation, test_utils::create_test_db};
+
+    use super::*;
+
+    const TEST_SIG: &str =
+        "5XgPzWKZSaC8phfRPDG55MMgxaDb35iNRfnPQEbd76nehutdKYU4Stp1ChKZtrjpQYSVZqs9az4p4RootDUwx8Ct";
+
+    fn count(conn: &Connection, table: &str) -> i64 {
+        conn.query_row(&format!("SELECT COUNT(*) FROM {table}"), [], |row| {
+            row.get(0)
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_save_and_clear() {
+        let conn = create_test_db();
+        let signature = Signature::from_str(TEST_SIG).unwrap();
+        let e: Box<dyn Error> = "bad".into();
+        let err = DecodeError::new(&signature, 2, Some(1), Some(3), e.as_ref());
+        err.save(&conn).unwrap();
+        // same instruction replaces
+        err.save(&conn).unwrap();
+        DecodeError::new(&signature, 2, None, None, e.as_ref())
+            .save(&conn)
+            .unwrap();
+        Invocation {
+            sig: TEST_SIG.into(),
+            signer: "def".into(),
+            ix: 1,
+            unix_timestamp: 2,
+            slot: 206389108,
+            cpi_prog: "".into(),
+            amount_in: 123,
+            amount_out: 456,
+            mint_in: "ghi".into(),
+            mint_out: "jkl".into(),
+        }
+        .save(&conn)
+        .unwrap();
+        assert_eq!(count(&conn, "decode_errors"), 2);
+        assert_eq!(
+            signatures_with_decode_errors(&conn).unwrap(),
+            vec![signature]
+        );
+
+        clear_signature(&conn, &signature).unwrap();
+        assert_eq!(count(&conn, "decode_errors"), 0);
+        assert_eq!(count(&conn, "invocations"), 0);
+        assert!(signatures_with_decode_errors(&conn).unwrap().is_empty());
+    }
+}
@@ -2,6 +2,7 @@ use std::error::Error;
 
 use borsh::BorshDeserialize;
 use rusqlite::Connection;
+use serde::Serialize;
 use solana_program::{message::SanitizedMessage, sysvar::instructions::BorrowedInstruction};
 use solana_sdk::signature::Signature;
 use solana_transaction_status::{
@@ -13,9 +14,18 @@ use stakedex_interface::{
     SWAP_VIA_STAKE_IX_DISCM,
 };
 
-use crate::subcmd::index::parse::{account_index_of, inner_instructions_of, token_balance_of};
+use crate::subcmd::index::{
+    layout::IxLayout,
+    parse::{account_index_of, inner_discriminant_of, inner_instructions_of, token_balance_of},
+};
+
+mod decode_error;
+mod raw_transaction;
 
-#[derive(Clone, Debug, PartialEq)]
+pub use decode_error::*;
+pub use raw_transaction::*;
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub struct Invocation {
     pub sig: String,
     pub signer: String,
@@ -29,55 +39,68 @@ pub struct Invocation {
     pub mint_out: String,
 }
 
+/// Result of decoding all stakedex invocations in a transaction
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Decoded {
+    pub invocations: Vec<Invocation>,
+    pub errors: Vec<DecodeError>,
+}
+
+impl Decoded {
+    pub fn save(&self, conn: &Connection) -> rusqlite::Result<()> {
+        for invocation in self.invocations.iter() {
+            invocation.save(conn)?;
+        }
+        for error in self.errors.iter() {
+            error.save(conn)?;
+        }
+        Ok(())
+    }
+}
+
 impl Invocation {
     pub fn decode(
         signature: &Signature,
         ectx: &EncodedConfirmedTransactionWithStatusMeta,
         smsg: &SanitizedMessage,
-    ) -> Vec<Self> {
+    ) -> Decoded {
+        let mut res = Decoded::default();
         let top_level_ixs = smsg.decompile_instructions();
-        let mut res = top_level_ixs
-            .iter()
-            .filter_map(|top_ix| {
-                match Self::try_decode_top_level_ix(top_ix, signature, ectx, smsg) {
-                    Ok(opt) => opt,
-                    Err(e) => {
-                        println!("WARN: {}", e);
-                        None
-                    }
-                }
-            })
-            .collect();
+        for (top_level_index, top_ix) in top_level_ixs.iter().enumerate() {
+            match Self::try_decode_top_level_ix(top_ix, signature, ectx, smsg) {
+                Ok(opt) => res.invocations.extend(opt),
+                Err(e) => res.errors.push(DecodeError::new(
+                    signature,
+                    top_level_index.try_into().unwrap(),
+                    None,
+                    top_ix.data.first().copied(),
+                    e.as_ref(),
+                )),
+            }
+        }
         let inner_ixs = match inner_instructions_of(ectx) {
             Some(slice) => slice,
             None => return res,
         };
-        let decoded_inner = inner_ixs.iter().flat_map(|top_level_ix| {
+        for top_level_ix in inner_ixs {
             let top_level_index = top_level_ix.index;
-            top_level_ix
-                .instructions
-                .iter()
-                .filter_map(move |inner_ix| {
-                    match Self::try_decode_inner_ix(
-                        inner_ix,
-                        top_level_index,
+            for (inner_index, inner_ix) in top_level_ix.instructions.iter().enumerate() {
+                match Self::try_decode_inner_ix(inner_ix, top_level_index, signature, ectx, smsg) {
+                    Ok(opt) => res.invocations.extend(opt),
+                    Err(e) => res.errors.push(DecodeError::new(
                         signature,
-                        ectx,
-                        smsg,
-                    ) {
-                        Ok(opt) => opt,
-                        Err(e) => {
-                            println!("WARN: {}", e);
-                            None
-                        }
-                    }
-                })
-        });
-        res.extend(decoded_inner);
+                        top_level_index,
+                        Some(inner_index),
+                        inner_discriminant_of(inner_ix),
+                        e.as_ref(),
+                    )),
+                }
+            }
+        }
         res
     }
 
-    fn try_decode_inner_ix(
+    pub fn try_decode_inner_ix(
         inner_ix: &UiInstruction,
         top_level_index: u8,
         signature: &Signature,
@@ -120,11 +143,10 @@ impl Invocation {
         };
         let inner_ix_data = bs58::decode(&inner_ix_compiled.data).into_vec()?;
         let ix = *inner_ix_data.first().ok_or("Empty data")?;
-        // luckily, signer is always the first account for all stakedex instructions
-        // since payer was removed
+        let layout = IxLayout::for_slot(ectx.slot)?;
         let signer_index = *inner_ix_compiled
             .accounts
-            .first()
+            .get(layout.signer_index())
             .ok_or("No signer index")?;
         let signer = account_keys
             .get(signer_index.into())
@@ -136,7 +158,7 @@ impl Invocation {
                 let amount_in = args.amount;
                 let dest_token_to_index = *inner_ix_compiled
                     .accounts
-                    .get(2)
+                    .get(layout.account_index(2))
                     .ok_or("SwapViaStake no dest_token_to index")?;
                 let dest_token_to_pre =
                     token_balance_of(pre_token_balances, dest_token_to_index).unwrap_or(0);
@@ -145,7 +167,7 @@ impl Invocation {
                 let amount_out = dest_token_to_post.saturating_sub(dest_token_to_pre);
                 let mint_in_index = *inner_ix_compiled
                     .accounts
-                    .get(5)
+                    .get(layout.account_index(5))
                     .ok_or("SwapViaStake no mint in index")?;
                 let mint_in = account_keys
                     .get(mint_in_index.into())
@@ -153,7 +175,7 @@ impl Invocation {
                     .to_string();
                 let mint_out_index = *inner_ix_compiled
                     .accounts
-                    .get(6)
+                    .get(layout.account_index(6))
                     .ok_or("SwapViaStake no mint out index")?;
                 let mint_out = account_keys
                     .get(mint_out_index.into())
@@ -166,7 +188,7 @@ impl Invocation {
                 let amount_in = args.amount;
                 let dest_token_to_index = *inner_ix_compiled
                     .accounts
-                    .get(2)
+                    .get(layout.account_index(2))
                     .ok_or("StakeWrappedSol no dest_token_to index")?;
                 let dest_token_to_pre =
                     token_balance_of(pre_token_balances, dest_token_to_index).unwrap_or(0);
@@ -175,7 +197,7 @@ impl Invocation {
                 let amount_out = dest_token_to_post.saturating_sub(dest_token_to_pre);
                 let mint_out_index = *inner_ix_compiled
                     .accounts
-                    .get(6)
+                    .get(layout.account_index(6))
                     .ok_or("StakeWrappedSol no mint out index")?;
                 let mint_out = account_keys
                     .get(mint_out_index.into())
@@ -186,7 +208,7 @@ impl Invocation {
             DEPOSIT_STAKE_IX_DISCM => {
                 let stake_acc_index = *inner_ix_compiled
                     .accounts
-                    .get(1)
+                    .get(layout.account_index(1))
                     .ok_or("DepositStake no stake account index")?;
                 let amount_in = *tx_meta
                     .pre_balances
@@ -194,7 +216,7 @@ impl Invocation {
                     .ok_or("DepositStake no stake acc pre_balance")?;
                 let dest_token_out_index = *inner_ix_compiled
                     .accounts
-                    .get(2)
+                    .get(layout.account_index(2))
                     .ok_or("DepositStake no dest_token_out index")?;
                 let dest_token_out_pre =
                     token_balance_of(pre_token_balances, dest_token_out_index).unwrap_or(0);
@@ -203,7 +225,7 @@ impl Invocation {
                 let amount_out = dest_token_out_post.saturating_sub(dest_token_out_pre);
                 let mint_out_index = *inner_ix_compiled
                     .accounts
-                    .get(4)
+                    .get(layout.account_index(4))
                     .ok_or("DepositStake no mint out index")?;
                 let mint_out = account_keys
                     .get(usize::from(mint_out_index))
@@ -230,7 +252,7 @@ impl Invocation {
 
     // TODO: refactor this to use CompiledInstruction instead of BorrowedInstruction
     // to share more code with try_decode_inner_ix
-    fn try_decode_top_level_ix(
+    pub fn try_decode_top_level_ix(
         top_ix: &BorrowedInstruction,
         signature: &Signature,
         ectx: &EncodedConfirmedTransactionWithStatusMeta,
@@ -247,6 +269,7 @@ impl Invocation {
             .ok_or("No transaction.meta")?;
         let unix_timestamp = ectx.block_time.ok_or("No meta.block_time")?;
         let ix = *top_ix.data.first().ok_or("Empty data")?;
+        let layout = IxLayout::for_slot(ectx.slot)?;
         let pre_token_balances = match &tx_meta.pre_token_balances {
             OptionSerializer::Some(v) => v,
             _ => return Err("no pre_token_balances".into()),
@@ -255,16 +278,14 @@ impl Invocation {
             OptionSerializer::Some(v) => v,
             _ => return Err("no post_token_balances".into()),
         };
-        // luckily, signer is always the first account for all stakedex instructions
-        // since payer was removed
         let accounts = &top_ix.accounts;
-        let signer = accounts.first().ok_or("No signer")?;
+        let signer = accounts.get(layout.signer_index()).ok_or("No signer")?;
         let (amount_in, amount_out, mint_in, mint_out) = match ix {
             SWAP_VIA_STAKE_IX_DISCM => {
                 let args = SwapViaStakeArgs::deserialize(&mut &top_ix.data[1..])?;
                 let amount_in = args.amount;
                 let dest_token_to = accounts
-                    .get(2)
+                    .get(layout.account_index(2))
                     .ok_or("SwapViaStake no dest_token_to")?
                     .pubkey;
                 let dest_token_to_index: u8 = account_index_of(&account_keys, dest_token_to)
@@ -277,12 +298,12 @@ impl Invocation {
                     token_balance_of(post_token_balances, dest_token_to_index).unwrap_or(0);
                 let amount_out = dest_token_to_post.saturating_sub(dest_token_to_pre);
                 let mint_in = accounts
-                    .get(5)
+                    .get(layout.account_index(5))
                     .ok_or("SwapViaStake no mint in")?
                     .pubkey
                     .to_string();
                 let mint_out = accounts
-                    .get(6)
+                    .get(layout.account_index(6))
                     .ok_or("SwapViaStake no mint out")?
                     .pubkey
                     .to_string();
@@ -292,7 +313,7 @@ impl Invocation {
                 let args = StakeWrappedSolArgs::deserialize(&mut &top_ix.data[1..])?;
                 let amount_in = args.amount;
                 let dest_token_to = accounts
-                    .get(2)
+                    .get(layout.account_index(2))
                     .ok_or("StakeWrappedSol no dest_token_to")?
                     .pubkey;
                 let dest_token_to_index: u8 = account_index_of(&account_keys, dest_token_to)
@@ -305,7 +326,7 @@ impl Invocation {
                     token_balance_of(post_token_balances, dest_token_to_index).unwrap_or(0);
                 let amount_out = dest_token_to_post.saturating_sub(dest_token_to_pre);
                 let mint_out = accounts
-                    .get(6)
+                    .get(layout.account_index(6))
                     .ok_or("StakeWrappedSol no mint out")?
                     .pubkey
                     .to_string();
@@ -313,7 +334,7 @@ impl Invocation {
             }
             DEPOSIT_STAKE_IX_DISCM => {
                 let stake_acc = accounts
-                    .get(1)
+                    .get(layout.account_index(1))
                     .ok_or("DepositStake no stake account")?
                     .pubkey;
                 let stake_acc_index = account_index_of(&account_keys, stake_acc)
@@ -323,7 +344,7 @@ impl Invocation {
                     .get(stake_acc_index)
                     .ok_or("DepositStake no stake acc pre_balance")?;
                 let dest_token_out = accounts
-                    .get(2)
+                    .get(layout.account_index(2))
                     .ok_or("DepositStake no dest token out")?
                     .pubkey;
                 let dest_token_out_index: u8 = account_index_of(&account_keys, dest_token_out)
@@ -336,7 +357,7 @@ impl Invocation {
                     token_balance_of(post_token_balances, dest_token_out_index).unwrap_or(0);
                 let amount_out = dest_token_out_post.saturating_sub(dest_token_out_pre);
                 let mint_out = accounts
-                    .get(4)
+                    .get(layout.account_index(4))
                     .ok_or("DepositStake no mint out")?
                     .pubkey
                     .to_string();
@@ -387,10 +408,17 @@ mod tests {
     use std::str::FromStr;
 
     use lazy_static::lazy_static;
-    use solana_client::nonblocking::rpc_client::RpcClient;
+    use solana_client::{
+        nonblocking::rpc_client::RpcClient, rpc_client::GetConfirmedSignaturesForAddress2Config,
+    };
+    use solana_program::pubkey::Pubkey;
+    use solana_sdk::commitment_config::CommitmentConfig;
+    use solana_transaction_status::UiTransactionTokenBalance;
     use stakedex_sdk_common::{bsol, cogentsol, jsol};
 
-    use crate::subcmd::index::{db::test_utils::create_test_db, parse::parse_b64_tx};
+    use crate::subcmd::index::{
+        consts::PAYER_REMOVED_SLOT, db::test_utils::create_test_db, fetch_tx, parse::parse_b64_tx,
+    };
 
     use super::*;
 
@@ -404,12 +432,20 @@ mod tests {
     const CPI_STAKE_WRAPPED_SOL_SIGNER: &str = "4pEhTh7CmBGYAwqExy6YoUFFjdwPXRVNkfkRMt1oCA6G";
     const JUP_PROGRAM_ID: &str = "JUP5cHjnnCx2DppVsufsLrXs8EBZeEZzGtEK9Gdz6ow";
 
+    /// Transaction in slot PAYER_REMOVED_SLOT where the program was upgraded to remove the payer
+    const PAYER_REMOVED_SIGNATURE: &str =
+        "tZL1zdBk5P8Q7V9m3qpZ8w8B5SgkSomAjSiEPm1tRKQJvNvaJPoPCougPF5JegAoEEnnZjMiuLzTryUUmSagxmG";
+
     async fn example_test(json_str: &str, expected: &Invocation) {
         let ectx: EncodedConfirmedTransactionWithStatusMeta =
             serde_json::from_str(json_str).unwrap();
         let (ectx, smsg) = parse_b64_tx(&RPC, ectx).await.unwrap();
         let signature = Signature::from_str(&expected.sig).unwrap();
-        let invocations = Invocation::decode(&signature, &ectx, &smsg);
+        let Decoded {
+            invocations,
+            errors,
+        } = Invocation::decode(&signature, &ectx, &smsg);
+        assert!(errors.is_empty());
         assert_eq!(invocations.len(), 1);
         let inv = &invocations[0];
         assert_eq!(inv, expected);
@@ -486,6 +522,67 @@ mod tests {
         }).await;
     }
 
+    /// Real mainnet transactions from before PAYER_REMOVED_SLOT, decoded in the WithPayer layout.
+    /// The decoded signer and mint_out must match the token account the transaction's own
+    /// token balances credit amount_out to, which only holds if the layout shift is right.
+    #[tokio::test]
+    async fn test_legacy_payer_invocations() {
+        let statuses = RPC
+            .get_signatures_for_address_with_config(
+                &stakedex_interface::ID,
+                GetConfirmedSignaturesForAddress2Config {
+                    before: Some(Signature::from_str(PAYER_REMOVED_SIGNATURE).unwrap()),
+                    until: None,
+                    limit: Some(50),
+                    commitment: Some(CommitmentConfig::finalized()),
+                },
+            )
+            .await
+            .unwrap();
+        let mut checked = 0;
+        for status in statuses.iter().filter(|s| s.err.is_none()) {
+            let signature = Signature::from_str(&status.signature).unwrap();
+            let ectx = fetch_tx(&RPC, &signature).await.unwrap();
+            let (ectx, smsg) = parse_b64_tx(&RPC, ectx).await.unwrap();
+            let Decoded {
+                invocations,
+                errors,
+            } = Invocation::decode(&signature, &ectx, &smsg);
+            assert!(errors.is_empty(), "{signature}: {errors:?}");
+            let meta = ectx.transaction.meta.as_ref().unwrap();
+            let balances = |b: &OptionSerializer<Vec<UiTransactionTokenBalance>>| {
+                Option::<Vec<_>>::from(b.clone()).unwrap_or_default()
+            };
+            let [pre, post] = [&meta.pre_token_balances, &meta.post_token_balances].map(balances);
+            // CPI invocations may have their output spent by the caller
+            for inv in invocations.iter().filter(|inv| inv.cpi_prog.is_empty()) {
+                assert!(inv.slot <= PAYER_REMOVED_SLOT);
+                let signer = Pubkey::from_str(&inv.signer).unwrap();
+                assert!(
+                    smsg.account_keys()
+                        .iter()
+                        .enumerate()
+                        .any(|(i, key)| *key == signer && smsg.is_signer(i)),
+                    "{signature}: decoded signer {signer} did not sign"
+                );
+                let dest = post
+                    .iter()
+                    .find(|b| {
+                        b.mint == inv.mint_out
+                            && Option::<&String>::from(b.owner.as_ref()) == Some(&inv.signer)
+                    })
+                    .unwrap_or_else(|| {
+                        panic!("{signature}: signer has no {} token account", inv.mint_out)
+                    });
+                let dest_pre = token_balance_of(&pre, dest.account_index).unwrap_or(0);
+                let dest_post = token_balance_of(&post, dest.account_index).unwrap();
+                assert_eq!(dest_post - dest_pre, inv.amount_out, "{signature}");
+                checked += 1;
+            }
+        }
+        assert!(checked > 0, "no invocations before PAYER_REMOVED_SLOT");
+    }
+
     #[tokio::test]
     async fn test_cpi_swap_via_stake() {
         let s = include_str!("../../examples/cpi_swap_via_stake.json");
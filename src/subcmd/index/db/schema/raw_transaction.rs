@@ -0,0 +1,92 @@
+use std::{
+    error::Error,
+    io::{Read, Write},
+    str::FromStr,
+};
+
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use rusqlite::{Connection, OptionalExtension};
+use solana_sdk::signature::Signature;
+use solana_transaction_status::EncodedConfirmedTransactionWithStatusMeta;
+
+pub fn archive_transaction(
+    conn: &Connection,
+    signature: &Signature,
+    ectx: &EncodedConfirmedTransactionWithStatusMeta,
+) -> Result<(), Box<dyn Error>> {
+    let json = serde_json::to_vec(ectx)?;
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&json)?;
+    let data = encoder.finish()?;
+    let mut stmt = conn.prepare_cached(
+        "INSERT OR REPLACE INTO raw_transactions (sig, slot, data) VALUES (?1, ?2, ?3)",
+    )?;
+    stmt.execute((signature.to_string(), ectx.slot, data))?;
+    Ok(())
+}
+
+/// Returns None if transaction not archived
+pub fn load_archived_transaction(
+    conn: &Connection,
+    signature: &Signature,
+) -> Result<Option<EncodedConfirmedTransactionWithStatusMeta>, Box<dyn Error>> {
+    let data: Vec<u8> = match conn
+        .query_row(
+            "SELECT data FROM raw_transactions WHERE sig = ?1",
+            [signature.to_string()],
+            |row| row.get(0),
+        )
+        .optional()?
+    {
+        Some(d) => d,
+        None => return Ok(None),
+    };
+    let mut json = Vec::new();
+    GzDecoder::new(data.as_slice()).read_to_end(&mut json)?;
+    Ok(Some(serde_json::from_slice(&json)?))
+}
+
+/// Signatures of all archived transactions, oldest first
+pub fn archived_signatures(conn: &Connection) -> Result<Vec<Signature>, Box<dyn Error>> {
+    let mut stmt = conn.prepare("SELECT sig FROM raw_transactions ORDER BY slot ASC")?;
+    let sigs = stmt.query_map([], |row| row.get::<_, String>(0))?;
+    let mut res = Vec::new();
+    for sig in sigs {
+        res.push(Signature::from_str(&sig?)?);
+    }
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::subcmd::index::db::test_utils::create_test_db;
+
+    use super::*;
+
+    const TEST_SIG: &str =
+        "5dFfnWJGVga8YnD8DpKcGLjXVyzbC7vwnULxUfrVB1uRauVfxKoEW8AufmezQET2mLvevuZNSz6Nu6o9wdmhQ6yL";
+
+    #[test]
+    fn test_archive_round_trip() {
+        let conn = create_test_db();
+        let signature = Signature::from_str(TEST_SIG).unwrap();
+        assert!(load_archived_transaction(&conn, &signature)
+            .unwrap()
+            .is_none());
+
+        let s = include_str!("../../examples/swap_via_stake.json");
+        let ectx: EncodedConfirmedTransactionWithStatusMeta = serde_json::from_str(s).unwrap();
+        archive_transaction(&conn, &signature, &ectx).unwrap();
+        // re-archiving replaces
+        archive_transaction(&conn, &signature, &ectx).unwrap();
+
+        let loaded = load_archived_transaction(&conn, &signature)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            serde_json::to_value(&loaded).unwrap(),
+            serde_json::to_value(&ectx).unwrap()
+        );
+        assert_eq!(archived_signatures(&conn).unwrap(), vec![signature]);
+    }
+}
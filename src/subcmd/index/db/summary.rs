@@ -0,0 +1,113 @@
+use std::error::Error;
+
+use rusqlite::Connection;
+use serde::Serialize;
+
+use super::mint_decimals::mints_missing_decimals;
+
+/// Totals of everything indexed in the DB
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct IndexSummary {
+    pub invocations: u64,
+
+    /// Distinct transactions of `invocations`
+    pub transactions: u64,
+
+    pub decode_errors: u64,
+
+    pub archived_transactions: u64,
+
+    /// None if DB empty
+    pub earliest_slot: Option<u64>,
+
+    /// None if DB empty
+    pub latest_slot: Option<u64>,
+
+    /// Mints of invocations whose decimals could not be fetched,
+    /// excluded from the invocation_amounts view's ui amounts
+    pub mints_missing_decimals: usize,
+}
+
+pub fn index_summary(conn: &Connection) -> Result<IndexSummary, Box<dyn Error>> {
+    let (invocations, transactions, earliest_slot, latest_slot) = conn.query_row(
+        "SELECT COUNT(*), COUNT(DISTINCT sig), MIN(slot), MAX(slot) FROM invocations",
+        [],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+    )?;
+    let decode_errors =
+        conn.query_row("SELECT COUNT(*) FROM decode_errors", [], |row| row.get(0))?;
+    let archived_transactions =
+        conn.query_row("SELECT COUNT(*) FROM raw_transactions", [], |row| {
+            row.get(0)
+        })?;
+    Ok(IndexSummary {
+        invocations,
+        transactions,
+        decode_errors,
+        archived_transactions,
+        earliest_slot,
+        latest_slot,
+        mints_missing_decimals: mints_missing_decimals(conn)?.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use stakedex_interface::SWAP_VIA_STAKE_IX_DISCM;
+    use stakedex_sdk_common::{jitosol, msol};
+
+    use crate::subcmd::index::db::{
+        mint_decimals::save_mint_decimals, schema::Invocation, test_utils::create_test_db,
+    };
+
+    use super::*;
+
+    fn invocation(sig: &str, slot: u64) -> Invocation {
+        Invocation {
+            sig: sig.into(),
+            signer: "def".into(),
+            ix: SWAP_VIA_STAKE_IX_DISCM,
+            unix_timestamp: 1,
+            slot,
+            cpi_prog: "".into(),
+            amount_in: 1,
+            amount_out: 1,
+            mint_in: jitosol::ID.to_string(),
+            mint_out: msol::ID.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_index_summary() {
+        let conn = create_test_db();
+        assert_eq!(
+            index_summary(&conn).unwrap(),
+            IndexSummary {
+                invocations: 0,
+                transactions: 0,
+                decode_errors: 0,
+                archived_transactions: 0,
+                earliest_slot: None,
+                latest_slot: None,
+                mints_missing_decimals: 0,
+            }
+        );
+
+        for (sig, slot) in [("a", 5), ("a", 5), ("b", 3)] {
+            invocation(sig, slot).save(&conn).unwrap();
+        }
+        save_mint_decimals(&conn, &jitosol::ID, 9).unwrap();
+        assert_eq!(
+            index_summary(&conn).unwrap(),
+            IndexSummary {
+                invocations: 3,
+                transactions: 2,
+                decode_errors: 0,
+                archived_transactions: 0,
+                earliest_slot: Some(3),
+                latest_slot: Some(5),
+                mints_missing_decimals: 1,
+            }
+        );
+    }
+}
@@ -0,0 +1,283 @@
+use std::{error::Error, fmt::Debug};
+
+use borsh::BorshDeserialize;
+use lazy_static::lazy_static;
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
+use stakedex_interface::{
+    CloseFeeTokenAccountIxArgs, CloseFeeTokenAccountKeys, CreateFeeTokenAccountIxArgs,
+    CreateFeeTokenAccountKeys, DepositStakeIxArgs, DepositStakeKeys, StakeWrappedSolIxArgs,
+    StakeWrappedSolKeys, SwapViaStakeIxArgs, SwapViaStakeKeys, WithdrawFeesIxArgs,
+    WithdrawFeesKeys, CLOSE_FEE_TOKEN_ACCOUNT_IX_ACCOUNTS_LEN, CLOSE_FEE_TOKEN_ACCOUNT_IX_DISCM,
+    CREATE_FEE_TOKEN_ACCOUNT_IX_ACCOUNTS_LEN, CREATE_FEE_TOKEN_ACCOUNT_IX_DISCM,
+    DEPOSIT_STAKE_IX_ACCOUNTS_LEN, DEPOSIT_STAKE_IX_DISCM, STAKE_WRAPPED_SOL_IX_ACCOUNTS_LEN,
+    STAKE_WRAPPED_SOL_IX_DISCM, SWAP_VIA_STAKE_IX_ACCOUNTS_LEN, SWAP_VIA_STAKE_IX_DISCM,
+    WITHDRAW_FEES_IX_ACCOUNTS_LEN, WITHDRAW_FEES_IX_DISCM,
+};
+
+use super::consts::{EARLIEST_SLOT, PAYER_REMOVED_SLOT};
+
+pub fn ix_name(ix: u8) -> &'static str {
+    match ix {
+        STAKE_WRAPPED_SOL_IX_DISCM => "StakeWrappedSol",
+        SWAP_VIA_STAKE_IX_DISCM => "SwapViaStake",
+        CREATE_FEE_TOKEN_ACCOUNT_IX_DISCM => "CreateFeeTokenAccount",
+        CLOSE_FEE_TOKEN_ACCOUNT_IX_DISCM => "CloseFeeTokenAccount",
+        WITHDRAW_FEES_IX_DISCM => "WithdrawFees",
+        DEPOSIT_STAKE_IX_DISCM => "DepositStake",
+        _ => "Unknown",
+    }
+}
+
+/// Debug format of an instruction's `*IxArgs` deserialized from `args_data`,
+/// the instruction data after the discriminant. None if they fail to deserialize
+pub fn ix_args(ix: u8, mut args_data: &[u8]) -> Option<String> {
+    fn debug_of<T: BorshDeserialize + Debug>(args_data: &mut &[u8]) -> Option<String> {
+        T::deserialize(args_data).ok().map(|a| format!("{a:?}"))
+    }
+    let args_data = &mut args_data;
+    match ix {
+        STAKE_WRAPPED_SOL_IX_DISCM => debug_of::<StakeWrappedSolIxArgs>(args_data),
+        SWAP_VIA_STAKE_IX_DISCM => debug_of::<SwapViaStakeIxArgs>(args_data),
+        CREATE_FEE_TOKEN_ACCOUNT_IX_DISCM => debug_of::<CreateFeeTokenAccountIxArgs>(args_data),
+        CLOSE_FEE_TOKEN_ACCOUNT_IX_DISCM => debug_of::<CloseFeeTokenAccountIxArgs>(args_data),
+        WITHDRAW_FEES_IX_DISCM => debug_of::<WithdrawFeesIxArgs>(args_data),
+        DEPOSIT_STAKE_IX_DISCM => debug_of::<DepositStakeIxArgs>(args_data),
+        _ => None,
+    }
+}
+
+/// Field names of a `stakedex_interface` `*Keys` struct, in the order of the instruction's accounts.
+///
+/// Every field is set to a distinct pubkey so that the `AccountMeta`s the interface converts the
+/// keys to can be mapped back to their field. The compiler checks that exactly the struct's fields
+/// are listed, and the interface decides their order.
+macro_rules! account_roles_of {
+    ($keys:ident, $len:ident { $($field:ident),+ $(,)? }) => {{
+        let names = [$(stringify!($field)),+];
+        let mut i = 0u8;
+        let keys = $keys {
+            $($field: {
+                i += 1;
+                Pubkey::new_from_array([i; 32])
+            }),+
+        };
+        let metas: [AccountMeta; $len] = (&keys).into();
+        metas
+            .iter()
+            .map(|meta| names[usize::from(meta.pubkey.to_bytes()[0] - 1)])
+            .collect::<Vec<&'static str>>()
+    }};
+}
+
+lazy_static! {
+    static ref STAKE_WRAPPED_SOL_ROLES: Vec<&'static str> = account_roles_of!(
+        StakeWrappedSolKeys,
+        STAKE_WRAPPED_SOL_IX_ACCOUNTS_LEN {
+            user,
+            wsol_from,
+            dest_token_to,
+            wsol_bridge_in,
+            sol_bridge_out,
+            dest_token_fee_token_account,
+            dest_token_mint,
+            wsol_mint,
+            token_program,
+            system_program,
+        }
+    );
+    static ref SWAP_VIA_STAKE_ROLES: Vec<&'static str> = account_roles_of!(
+        SwapViaStakeKeys,
+        SWAP_VIA_STAKE_IX_ACCOUNTS_LEN {
+            user,
+            src_token_from,
+            dest_token_to,
+            bridge_stake,
+            dest_token_fee_token_account,
+            src_token_mint,
+            dest_token_mint,
+        }
+    );
+    static ref DEPOSIT_STAKE_ROLES: Vec<&'static str> = account_roles_of!(
+        DepositStakeKeys,
+        DEPOSIT_STAKE_IX_ACCOUNTS_LEN {
+            user,
+            stake_account,
+            dest_token_to,
+            dest_token_fee_token_account,
+            dest_token_mint,
+        }
+    );
+    static ref CREATE_FEE_TOKEN_ACCOUNT_ROLES: Vec<&'static str> = account_roles_of!(
+        CreateFeeTokenAccountKeys,
+        CREATE_FEE_TOKEN_ACCOUNT_IX_ACCOUNTS_LEN {
+            payer,
+            fee_token_account,
+            mint,
+            token_program,
+            system_program,
+        }
+    );
+    static ref CLOSE_FEE_TOKEN_ACCOUNT_ROLES: Vec<&'static str> = account_roles_of!(
+        CloseFeeTokenAccountKeys,
+        CLOSE_FEE_TOKEN_ACCOUNT_IX_ACCOUNTS_LEN {
+            admin,
+            fee_token_account,
+            close_to,
+            mint,
+            token_program,
+        }
+    );
+    static ref WITHDRAW_FEES_ROLES: Vec<&'static str> = account_roles_of!(
+        WithdrawFeesKeys,
+        WITHDRAW_FEES_IX_ACCOUNTS_LEN {
+            admin,
+            mint,
+            fee_token_account,
+            withdraw_to,
+            token_program,
+        }
+    );
+}
+
+/// Names of the fixed accounts of an instruction in the current (NoPayer) layout.
+/// Accounts after these are the pool-specific remaining accounts.
+pub fn account_roles(ix: u8) -> &'static [&'static str] {
+    match ix {
+        STAKE_WRAPPED_SOL_IX_DISCM => &STAKE_WRAPPED_SOL_ROLES,
+        SWAP_VIA_STAKE_IX_DISCM => &SWAP_VIA_STAKE_ROLES,
+        DEPOSIT_STAKE_IX_DISCM => &DEPOSIT_STAKE_ROLES,
+        CREATE_FEE_TOKEN_ACCOUNT_IX_DISCM => &CREATE_FEE_TOKEN_ACCOUNT_ROLES,
+        CLOSE_FEE_TOKEN_ACCOUNT_IX_DISCM => &CLOSE_FEE_TOKEN_ACCOUNT_ROLES,
+        WITHDRAW_FEES_IX_DISCM => &WITHDRAW_FEES_ROLES,
+        _ => &[],
+    }
+}
+
+/// The account layout of stakedex's swap instructions.
+/// This changed over the program's lifetime, so the layout to decode an instruction with
+/// depends on the slot it was executed in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IxLayout {
+    /// Before PAYER_REMOVED_SLOT, a payer account preceded the signer
+    /// and every other account was shifted back by one
+    WithPayer,
+
+    /// Current layout, signer is always the first account
+    NoPayer,
+}
+
+impl IxLayout {
+    pub fn for_slot(slot: u64) -> Result<Self, Box<dyn Error>> {
+        if slot < EARLIEST_SLOT {
+            return Err(format!("slot {slot} predates stakedex program").into());
+        }
+        // program was upgraded in PAYER_REMOVED_SLOT,
+        // upgrades only take effect from the next slot onwards
+        match slot <= PAYER_REMOVED_SLOT {
+            true => Ok(Self::WithPayer),
+            false => Ok(Self::NoPayer),
+        }
+    }
+
+    /// Maps the index of an account in the current (NoPayer) layout
+    /// to its index in this layout
+    pub const fn account_index(&self, current_index: usize) -> usize {
+        match self {
+            Self::WithPayer => current_index + 1,
+            Self::NoPayer => current_index,
+        }
+    }
+
+    pub const fn signer_index(&self) -> usize {
+        self.account_index(0)
+    }
+
+    /// Name of the account at `index` of instruction `ix` in this layout
+    pub fn account_role(&self, ix: u8, index: usize) -> String {
+        let current_index = match (self, index) {
+            (Self::WithPayer, 0) => return "payer".into(),
+            (Self::WithPayer, i) => i - 1,
+            (Self::NoPayer, i) => i,
+        };
+        let roles = account_roles(ix);
+        match roles.get(current_index) {
+            Some(role) => (*role).into(),
+            None => format!("remaining[{}]", current_index - roles.len()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_for_slot() {
+        assert!(IxLayout::for_slot(EARLIEST_SLOT - 1).is_err());
+        assert_eq!(
+            IxLayout::for_slot(EARLIEST_SLOT).unwrap(),
+            IxLayout::WithPayer
+        );
+        assert_eq!(
+            IxLayout::for_slot(PAYER_REMOVED_SLOT).unwrap(),
+            IxLayout::WithPayer
+        );
+        assert_eq!(
+            IxLayout::for_slot(PAYER_REMOVED_SLOT + 1).unwrap(),
+            IxLayout::NoPayer
+        );
+    }
+
+    #[test]
+    fn test_account_index() {
+        assert_eq!(IxLayout::WithPayer.signer_index(), 1);
+        assert_eq!(IxLayout::NoPayer.signer_index(), 0);
+        assert_eq!(IxLayout::WithPayer.account_index(6), 7);
+        assert_eq!(IxLayout::NoPayer.account_index(6), 6);
+    }
+
+    #[test]
+    fn test_ix_args() {
+        let args = ix_args(
+            SWAP_VIA_STAKE_IX_DISCM,
+            &[0, 47, 104, 89, 0, 0, 0, 0, 7, 0, 0, 0],
+        );
+        assert!(args.as_ref().unwrap().contains("amount: 1500000000"));
+        assert!(args.unwrap().contains("bridge_stake_seed: 7"));
+        assert_eq!(
+            ix_args(WITHDRAW_FEES_IX_DISCM, &[]).unwrap(),
+            "WithdrawFeesIxArgs"
+        );
+        // truncated amount
+        assert!(ix_args(STAKE_WRAPPED_SOL_IX_DISCM, &[0, 47]).is_none());
+        assert!(ix_args(u8::MAX, &[]).is_none());
+    }
+
+    #[test]
+    fn test_account_role() {
+        assert_eq!(
+            IxLayout::WithPayer.account_role(SWAP_VIA_STAKE_IX_DISCM, 0),
+            "payer"
+        );
+        assert_eq!(
+            IxLayout::WithPayer.account_role(SWAP_VIA_STAKE_IX_DISCM, 1),
+            "user"
+        );
+        assert_eq!(
+            IxLayout::NoPayer.account_role(SWAP_VIA_STAKE_IX_DISCM, 6),
+            "dest_token_mint"
+        );
+        assert_eq!(
+            IxLayout::NoPayer.account_role(SWAP_VIA_STAKE_IX_DISCM, 8),
+            "remaining[1]"
+        );
+        assert_eq!(
+            IxLayout::WithPayer.account_role(DEPOSIT_STAKE_IX_DISCM, 6),
+            "remaining[0]"
+        );
+        assert_eq!(
+            IxLayout::NoPayer.account_role(WITHDRAW_FEES_IX_DISCM, 2),
+            "fee_token_account"
+        );
+    }
+}
@@ -1,25 +1,43 @@
-use std::{path::PathBuf, str::FromStr, time::Duration};
+use std::{error::Error, path::PathBuf, str::FromStr, time::Duration};
 
-use clap::Args;
+use clap::{Args, Subcommand};
+use rusqlite::Connection;
 use solana_client::{
-    rpc_client::GetConfirmedSignaturesForAddress2Config, rpc_config::RpcTransactionConfig,
-    rpc_response::RpcConfirmedTransactionStatusWithSignature,
+    nonblocking::rpc_client::RpcClient, rpc_client::GetConfirmedSignaturesForAddress2Config,
+    rpc_config::RpcTransactionConfig, rpc_response::RpcConfirmedTransactionStatusWithSignature,
 };
 use solana_sdk::{commitment_config::CommitmentConfig, signature::Signature};
-use solana_transaction_status::UiTransactionEncoding;
+use solana_transaction_status::{EncodedConfirmedTransactionWithStatusMeta, UiTransactionEncoding};
 use tokio::runtime::Runtime;
 
 use self::{
-    consts::{FIRST_NON_ADMIN_SIGNATURE_SINCE_PAYER_REMOVED, MAX_SIGNATURES_FOR_ADDRESS_LIMIT},
-    db::{create_conn, earliest_indexed_signature, latest_indexed_signature, schema::Invocation},
-    parse::parse_b64_tx,
+    consts::MAX_SIGNATURES_FOR_ADDRESS_LIMIT,
+    db::{
+        create_conn, earliest_indexed_signature,
+        labels::{load_labels_file, seed_labels},
+        latest_indexed_signature,
+        mint_decimals::{mints_missing_decimals, save_mint_decimals},
+        schema::{archive_transaction, Decoded, Invocation},
+        summary::{index_summary, IndexSummary},
+    },
+    parse::{parse_archived_b64_tx, parse_b64_tx},
+    redecode::RedecodeArgs,
+    reprocess_errors::ReprocessErrorsArgs,
+};
+
+use crate::{
+    output::{print_output, CmdOutput},
+    token_utils::fetch_mint_decimals,
 };
 
 use super::SubcmdExec;
 
 mod consts;
-mod db;
-mod parse;
+pub mod db;
+pub mod layout;
+pub mod parse;
+mod redecode;
+mod reprocess_errors;
 
 #[derive(Args, Debug)]
 #[command(
@@ -30,7 +48,8 @@ pub struct IndexArgs {
         long,
         short,
         help = "Path to sqlite file to save data to",
-        default_value = "stakedex.sqlite"
+        default_value = "stakedex.sqlite",
+        global = true
     )]
     pub sqlite_file: PathBuf,
 
@@ -41,30 +60,117 @@ pub struct IndexArgs {
         default_value_t = false
     )]
     pub latest_only: bool,
+
+    #[arg(
+        long,
+        help = "Path to JSON file of {\"<address>\": \"<label>\"} to label addresses with in the DB's views. Takes precedence over the solana CLI config's address_labels"
+    )]
+    pub labels_file: Option<PathBuf>,
+
+    #[arg(
+        long,
+        help = "Also archive the raw transactions into the DB so that they can be re-decoded with `index redecode` without RPC",
+        default_value_t = false
+    )]
+    pub archive: bool,
+
+    #[command(subcommand)]
+    pub subcmd: Option<IndexSubcmd>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum IndexSubcmd {
+    Redecode(RedecodeArgs),
+    ReprocessErrors(ReprocessErrorsArgs),
 }
 
 impl SubcmdExec for IndexArgs {
     fn process_cmd(&self, args: &crate::Args) {
         let db = create_conn(&self.sqlite_file);
-        let until_limit_sig =
-            Signature::from_str(FIRST_NON_ADMIN_SIGNATURE_SINCE_PAYER_REMOVED).unwrap();
-        let (mut before_sig_opt, until_sig) = match self.latest_only {
-            true => (
-                None,
-                latest_indexed_signature(&db)
-                    .unwrap()
-                    .unwrap_or(until_limit_sig),
+        let file_labels = self
+            .labels_file
+            .as_ref()
+            .map(|path| load_labels_file(path).unwrap())
+            .unwrap_or_default();
+        seed_labels(
+            &db,
+            &args.mint_registry(),
+            &[&args.config.address_labels, &file_labels],
+        )
+        .unwrap();
+        match &self.subcmd {
+            Some(IndexSubcmd::Redecode(a)) => a.process_cmd(args, &db),
+            Some(IndexSubcmd::ReprocessErrors(a)) => a.process_cmd(args, &db),
+            None => self.index(args, &db),
+        }
+        seed_mint_decimals(args, &db);
+        print_output(args.output, &index_summary(&db).unwrap());
+    }
+}
+
+impl CmdOutput for IndexSummary {
+    type Row = Self;
+
+    fn text(&self) -> String {
+        let mut lines = vec![
+            format!(
+                "Invocations: {} in {} transactions",
+                self.invocations, self.transactions
             ),
-            false => (earliest_indexed_signature(&db).unwrap(), until_limit_sig),
-        };
-        let get_transaction_cfg = RpcTransactionConfig {
-            encoding: Some(UiTransactionEncoding::Base64),
-            commitment: Some(CommitmentConfig::finalized()),
-            max_supported_transaction_version: Some(0),
+            format!("Decode errors: {}", self.decode_errors),
+            format!("Archived transactions: {}", self.archived_transactions),
+        ];
+        if let (Some(earliest), Some(latest)) = (self.earliest_slot, self.latest_slot) {
+            lines.push(format!("Slots: {earliest} - {latest}"));
+        }
+        if self.mints_missing_decimals > 0 {
+            lines.push(format!(
+                "[WARN] {} mints missing decimals",
+                self.mints_missing_decimals
+            ));
+        }
+        lines.join("\n")
+    }
+
+    fn csv_rows(&self) -> Vec<&Self> {
+        vec![self]
+    }
+}
+
+/// Saves the decimals of all mints in invocations that dont have them saved yet
+fn seed_mint_decimals(args: &crate::Args, db: &Connection) {
+    let mints = mints_missing_decimals(db).unwrap();
+    if mints.is_empty() {
+        return;
+    }
+    let decimals = match fetch_mint_decimals(&args.config.rpc_client(), &mints) {
+        Ok(d) => d,
+        Err(e) => {
+            args.output
+                .log(format!("[WARN] Failed to fetch mint decimals: {e}"));
+            return;
+        }
+    };
+    for (mint, opt) in mints.iter().zip(decimals) {
+        match opt {
+            Some(d) => save_mint_decimals(db, mint, d).unwrap(),
+            None => args
+                .output
+                .log(format!("[WARN] Could not fetch decimals of mint {mint}")),
+        }
+    }
+}
+
+impl IndexArgs {
+    fn index(&self, args: &crate::Args, db: &Connection) {
+        // until = None indexes all the way back to program launch
+        let (mut before_sig_opt, until_sig_opt) = match self.latest_only {
+            true => (None, latest_indexed_signature(db).unwrap()),
+            false => (earliest_indexed_signature(db).unwrap(), None),
         };
         let rpc = args.config.nonblocking_rpc_client();
         let rt = Runtime::new().unwrap();
-        rt.block_on(async move {
+        rt.block_on(async {
             // TODO: split this into pipelines:
             // - 1 task for getSignaturesForAddress
             // - multiple tasks for getTransaction
@@ -77,7 +183,7 @@ impl SubcmdExec for IndexArgs {
                         &stakedex_interface::ID,
                         GetConfirmedSignaturesForAddress2Config {
                             before: before_sig_opt,
-                            until: Some(until_sig),
+                            until: until_sig_opt,
                             limit: Some(MAX_SIGNATURES_FOR_ADDRESS_LIMIT),
                             commitment: Some(CommitmentConfig::finalized()),
                         },
@@ -85,7 +191,7 @@ impl SubcmdExec for IndexArgs {
                     .await
                     .unwrap();
                 if get_signatures_res.is_empty() {
-                    println!("All transactions indexed");
+                    args.output.log("All transactions indexed");
                     break;
                 }
                 for RpcConfirmedTransactionStatusWithSignature { signature, err, .. } in
@@ -94,16 +200,19 @@ impl SubcmdExec for IndexArgs {
                     let signature = Signature::from_str(&signature).unwrap();
                     before_sig_opt.replace(signature);
                     if err.is_none() {
-                        let ectx = rpc
-                            .get_transaction_with_config(&signature, get_transaction_cfg)
-                            .await
-                            .unwrap();
+                        let ectx = fetch_tx(&rpc, &signature).await.unwrap();
+                        if self.archive {
+                            archive_transaction(db, &signature, &ectx).unwrap();
+                        }
                         let (ectx, smsg) = parse_b64_tx(&rpc, ectx).await.unwrap();
-                        let invocations = Invocation::decode(&signature, &ectx, &smsg);
-                        for invocation in invocations {
-                            invocation.save(&db).unwrap();
+                        let decoded = Invocation::decode(&signature, &ectx, &smsg);
+                        decoded.save(db).unwrap();
+                        match decoded.errors.len() {
+                            0 => args.output.log(format!("Indexed {signature}")),
+                            n => args
+                                .output
+                                .log(format!("Indexed {signature} with {n} decode errors")),
                         }
-                        println!("Indexed {signature}");
                     }
                 }
             }
@@ -111,3 +220,36 @@ impl SubcmdExec for IndexArgs {
         rt.shutdown_timeout(Duration::from_secs(5));
     }
 }
+
+async fn fetch_tx(
+    rpc: &RpcClient,
+    signature: &Signature,
+) -> Result<EncodedConfirmedTransactionWithStatusMeta, Box<dyn Error>> {
+    Ok(rpc
+        .get_transaction_with_config(
+            signature,
+            RpcTransactionConfig {
+                encoding: Some(UiTransactionEncoding::Base64),
+                commitment: Some(CommitmentConfig::finalized()),
+                max_supported_transaction_version: Some(0),
+            },
+        )
+        .await?)
+}
+
+async fn fetch_and_decode(
+    rpc: &RpcClient,
+    signature: &Signature,
+) -> Result<Decoded, Box<dyn Error>> {
+    let ectx = fetch_tx(rpc, signature).await?;
+    let (ectx, smsg) = parse_b64_tx(rpc, ectx).await?;
+    Ok(Invocation::decode(signature, &ectx, &smsg))
+}
+
+fn decode_archived(
+    signature: &Signature,
+    ectx: EncodedConfirmedTransactionWithStatusMeta,
+) -> Result<Decoded, Box<dyn Error>> {
+    let (ectx, smsg) = parse_archived_b64_tx(ectx)?;
+    Ok(Invocation::decode(signature, &ectx, &smsg))
+}
@@ -1,4 +1,4 @@
-use std::error::Error;
+use std::{error::Error, str::FromStr};
 
 use solana_client::nonblocking::rpc_client::RpcClient;
 use solana_program::{
@@ -10,7 +10,7 @@ use solana_program::{
 };
 use solana_transaction_status::{
     option_serializer::OptionSerializer, EncodedConfirmedTransactionWithStatusMeta,
-    UiInnerInstructions, UiTransactionTokenBalance,
+    UiInnerInstructions, UiInstruction, UiLoadedAddresses, UiTransactionTokenBalance,
 };
 
 use crate::global_lut_cache::get_lut;
@@ -80,6 +80,46 @@ pub async fn parse_b64_tx(
     ))
 }
 
+/// Same as [`parse_b64_tx`], but resolves address lookup tables using
+/// `meta.loaded_addresses` instead of fetching them from RPC.
+/// Used for re-decoding archived transactions offline.
+pub fn parse_archived_b64_tx(
+    ectx: EncodedConfirmedTransactionWithStatusMeta,
+) -> Result<(EncodedConfirmedTransactionWithStatusMeta, SanitizedMessage), Box<dyn Error>> {
+    let versioned_tx = ectx
+        .transaction
+        .transaction
+        .decode()
+        .ok_or("could not decode tx".to_owned())?;
+    let sanitized = SanitizedVersionedMessage::try_new(versioned_tx.message)?;
+    let simple_addr_loader = match sanitized.message.address_table_lookups() {
+        None => SimpleAddressLoader::Disabled,
+        Some(_) => {
+            let meta = ectx
+                .transaction
+                .meta
+                .as_ref()
+                .ok_or("No transaction.meta")?;
+            let UiLoadedAddresses { writable, readonly } = match &meta.loaded_addresses {
+                OptionSerializer::Some(l) => l,
+                _ => return Err("no loaded_addresses".into()),
+            };
+            let [writable, readonly] = [writable, readonly].map(|addrs| {
+                addrs
+                    .iter()
+                    .map(|a| Pubkey::from_str(a))
+                    .collect::<Result<Vec<_>, _>>()
+            });
+            SimpleAddressLoader::Enabled(LoadedAddresses {
+                writable: writable?,
+                readonly: readonly?,
+            })
+        }
+    };
+    let smsg = SanitizedMessage::try_new(sanitized, simple_addr_loader)?;
+    Ok((ectx, smsg))
+}
+
 pub fn account_index_of(account_keys: &AccountKeys, pk: &Pubkey) -> Option<usize> {
     for (i, maybe_pk) in account_keys.iter().enumerate() {
         if maybe_pk == pk {
@@ -113,6 +153,14 @@ pub fn inner_instructions_of(
     }
 }
 
+/// Returns None if not a compiled instruction or empty instruction data
+pub fn inner_discriminant_of(inner_ix: &UiInstruction) -> Option<u8> {
+    match inner_ix {
+        UiInstruction::Compiled(ix) => bs58::decode(&ix.data).into_vec().ok()?.first().copied(),
+        UiInstruction::Parsed(..) => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -135,4 +183,14 @@ mod tests {
         // accounts[4] should be saber prog
         assert_eq!(saber_stable_swap_prog::ID, *ixs[4].accounts[4].pubkey);
     }
+
+    #[test]
+    fn test_parse_archived_b64_tx() {
+        let s = include_str!("examples/cpi_swap_via_stake.json");
+        let ectx = serde_json::from_str(s).unwrap();
+        let (_ectx, msg) = parse_archived_b64_tx(ectx).unwrap();
+        let ixs = msg.decompile_instructions();
+        assert_eq!(6, ixs.len());
+        assert_eq!(saber_stable_swap_prog::ID, *ixs[4].accounts[4].pubkey);
+    }
 }
@@ -0,0 +1,34 @@
+use clap::Args;
+use rusqlite::Connection;
+
+use super::{
+    db::schema::{archived_signatures, clear_signature, load_archived_transaction},
+    decode_archived,
+};
+
+#[derive(Args, Debug)]
+#[command(
+    long_about = "Rebuild invocations and decode errors purely from the raw transaction archive, without RPC. Only transactions indexed with --archive are rebuilt, rows of other transactions are left untouched."
+)]
+pub struct RedecodeArgs;
+
+impl RedecodeArgs {
+    pub fn process_cmd(&self, args: &crate::Args, db: &Connection) {
+        let signatures = archived_signatures(db).unwrap();
+        // single sqlite transaction so that a failed redecode leaves the DB unchanged
+        let tx = db.unchecked_transaction().unwrap();
+        let mut n_errors = 0;
+        for signature in signatures.iter() {
+            let ectx = load_archived_transaction(&tx, signature).unwrap().unwrap();
+            let decoded = decode_archived(signature, ectx).unwrap();
+            clear_signature(&tx, signature).unwrap();
+            decoded.save(&tx).unwrap();
+            n_errors += decoded.errors.len();
+        }
+        tx.commit().unwrap();
+        args.output.log(format!(
+            "Redecoded {} archived transactions with {n_errors} decode errors",
+            signatures.len()
+        ));
+    }
+}
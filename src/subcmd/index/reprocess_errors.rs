@@ -0,0 +1,50 @@
+use std::time::Duration;
+
+use clap::Args;
+use rusqlite::Connection;
+use tokio::runtime::Runtime;
+
+use super::{
+    db::schema::{clear_signature, load_archived_transaction, signatures_with_decode_errors},
+    decode_archived, fetch_and_decode,
+};
+
+#[derive(Args, Debug)]
+#[command(
+    long_about = "Retry decoding all transactions with decode errors in the DB, e.g. after a decoder upgrade. Clears the errors of instructions that now decode successfully. Uses the raw transaction archive if available, else refetches from RPC."
+)]
+pub struct ReprocessErrorsArgs;
+
+impl ReprocessErrorsArgs {
+    pub fn process_cmd(&self, args: &crate::Args, db: &Connection) {
+        let signatures = signatures_with_decode_errors(db).unwrap();
+        let total = signatures.len();
+        let rpc = args.config.nonblocking_rpc_client();
+        let rt = Runtime::new().unwrap();
+        rt.block_on(async {
+            let mut fixed = 0;
+            for signature in signatures {
+                let decoded = match load_archived_transaction(db, &signature).unwrap() {
+                    Some(ectx) => decode_archived(&signature, ectx).unwrap(),
+                    None => fetch_and_decode(&rpc, &signature).await.unwrap(),
+                };
+                let tx = db.unchecked_transaction().unwrap();
+                clear_signature(&tx, &signature).unwrap();
+                decoded.save(&tx).unwrap();
+                tx.commit().unwrap();
+                match decoded.errors.len() {
+                    0 => {
+                        fixed += 1;
+                        args.output.log(format!("Fixed {signature}"));
+                    }
+                    n => args
+                        .output
+                        .log(format!("{signature} still has {n} decode errors")),
+                }
+            }
+            args.output
+                .log(format!("{fixed}/{total} transactions fixed"));
+        });
+        rt.shutdown_timeout(Duration::from_secs(5));
+    }
+}
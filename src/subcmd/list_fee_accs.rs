@@ -1,64 +1,511 @@
 use clap::Args;
 use itertools::izip;
-use solana_program::{program_pack::Pack, pubkey::Pubkey};
+use serde::Serialize;
+use solana_account_decoder::{UiAccountEncoding, UiDataSliceConfig};
+use solana_client::{
+    rpc_client::RpcClient,
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, RpcFilterType},
+};
+use solana_program::{native_token::lamports_to_sol, program_pack::Pack, pubkey::Pubkey};
 use spl_token::amount_to_ui_amount;
-use stakedex_sdk_common::{
-    bsol, cogentsol, daosol, esol, find_fee_token_acc, jitosol, jsol, lainesol, msol, risksol,
-    scnsol, stsol,
+use spl_token_2022::{extension::AccountType, state::Account as TokenAccount};
+use stakedex_sdk_common::find_fee_token_acc;
+
+use crate::{
+    mint_registry::MintRegistry,
+    output::{print_output, CmdOutput},
+    pool_utils::{fetch_exchange_rates, ExchangeRate},
+    token_utils::{
+        fetch_mint_decimals, get_multiple_accounts_chunked, is_token_program, mint_decimals_of,
+        unpack_token_account,
+    },
 };
 
 use super::SubcmdExec;
 
 #[derive(Args, Debug)]
-#[command(long_about = "List all fee token accounts for all xSOL mints")]
-pub struct ListFeeAccsArgs;
+#[command(long_about = "List all fee token accounts for all xSOL mints in the mint registry")]
+pub struct ListFeeAccsArgs {
+    #[arg(
+        long,
+        help = "Also scan all spl-token and Token-2022 accounts with getProgramAccounts to find fee token accounts of mints not in the mint registry. Requires an RPC that serves getProgramAccounts for the token programs",
+        default_value_t = false
+    )]
+    pub discover: bool,
+}
 
 impl SubcmdExec for ListFeeAccsArgs {
     fn process_cmd(&self, args: &crate::Args) {
         let client = args.config.rpc_client();
-        let addrs = ALL_XSOL_MINTS.map(|token_mint| find_fee_token_acc(&token_mint).0);
+        let registry = args.mint_registry();
+        let addrs: Vec<_> = registry
+            .mints
+            .iter()
+            .map(|info| find_fee_token_acc(&info.mint).0)
+            .collect();
 
-        let fetched = client.get_multiple_accounts(&addrs).unwrap();
-        let iter = izip!(ALL_XSOL_MINTS, addrs, fetched);
-        println!("Token | Address | Balance");
-        for (mint, addr, opt) in iter {
+        // fee accs and mints in one batch
+        let mut fetched =
+            get_multiple_accounts_chunked(&client, &[addrs.clone(), registry.mints()].concat())
+                .unwrap();
+        let fetched_mints = fetched.split_off(addrs.len());
+        let rates = fetch_exchange_rates(&client, &registry.mints).unwrap();
+        let iter = izip!(&registry.mints, addrs, fetched, fetched_mints, rates);
+        let mut fee_accounts = Vec::new();
+        for (info, addr, opt, mint_opt, rate) in iter {
+            let mint = info.mint;
             let acc = match opt {
                 Some(a) => a,
                 None => {
-                    println!("[WARN] Missing fee acc {} for token {}", addr, mint);
+                    args.output.log(format!(
+                        "[WARN] Missing fee acc {} for token {}",
+                        addr, mint
+                    ));
                     continue;
                 }
             };
-            let parsed = spl_token::state::Account::unpack(&acc.data).unwrap();
+            let parsed = unpack_token_account(&acc.data).unwrap();
             if parsed.mint != mint {
-                println!(
+                args.output.log(format!(
                     "[WARN] Wrong mint for acc {}. Expected: {}, got: {}",
                     addr, mint, parsed.mint
-                );
+                ));
+                continue;
+            }
+            if let FeeAccStatus::Invalid(e) = fee_acc_status(&registry, &addr, &parsed) {
+                args.output.log(format!("[WARN] {}", e));
                 continue;
             }
-            // TODO: change decimals if there exists xSOL not of 9 decimals
-            println!(
-                "{} | {} | {}",
-                mint,
-                addr,
-                amount_to_ui_amount(parsed.amount, spl_token::native_mint::DECIMALS)
+            let decimals = match mint_decimals_of(mint_opt.as_ref()) {
+                Some(d) => d,
+                None => {
+                    args.output.log(format!(
+                        "[WARN] Could not fetch decimals of mint {}, using registry's {}",
+                        mint, info.decimals
+                    ));
+                    info.decimals
+                }
+            };
+            let rate = match rate {
+                Ok(r) => Some(r),
+                Err(e) => {
+                    args.output.log(format!(
+                        "[WARN] Could not value {} in SOL: {}",
+                        info.symbol, e
+                    ));
+                    None
+                }
+            };
+            fee_accounts.push(FeeAcc::new(
+                &mint,
+                Some(info.symbol.clone()),
+                &addr,
+                parsed.amount,
+                decimals,
+                rate.as_ref(),
+            ));
+        }
+
+        if self.discover {
+            fee_accounts.extend(discover(args, &client, &registry));
+        }
+
+        print_output(args.output, &ListFeeAccsOutput::new(fee_accounts));
+    }
+}
+
+/// A fee token account and its balance
+#[derive(Debug, Serialize)]
+pub struct FeeAcc {
+    pub mint: String,
+
+    /// None if the mint is not in the mint registry
+    pub symbol: Option<String>,
+
+    pub address: String,
+
+    /// In token atomics
+    pub amount: u64,
+
+    pub decimals: u8,
+
+    /// `amount` in tokens
+    pub balance: f64,
+
+    /// `amount` in SOL at the mint's stake pool's current exchange rate.
+    /// None if the exchange rate is unknown
+    pub sol_value: Option<f64>,
+}
+
+impl FeeAcc {
+    pub fn new(
+        mint: &Pubkey,
+        symbol: Option<String>,
+        address: &Pubkey,
+        amount: u64,
+        decimals: u8,
+        rate: Option<&ExchangeRate>,
+    ) -> Self {
+        Self {
+            mint: mint.to_string(),
+            symbol,
+            address: address.to_string(),
+            amount,
+            decimals,
+            balance: amount_to_ui_amount(amount, decimals),
+            sol_value: rate.map(|r| lamports_to_sol(r.lamports_for(amount))),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ListFeeAccsOutput {
+    /// Fee token accounts of the mint registry's mints,
+    /// followed by those of unknown mints found with --discover
+    pub fee_accounts: Vec<FeeAcc>,
+
+    /// Sum of the fee token accounts' `sol_value`s, excluding those that are unknown
+    pub total_sol_value: f64,
+}
+
+impl ListFeeAccsOutput {
+    pub fn new(fee_accounts: Vec<FeeAcc>) -> Self {
+        let total_sol_value = fee_accounts.iter().filter_map(|acc| acc.sol_value).sum();
+        Self {
+            fee_accounts,
+            total_sol_value,
+        }
+    }
+}
+
+impl CmdOutput for ListFeeAccsOutput {
+    type Row = FeeAcc;
+
+    fn text(&self) -> String {
+        let mut lines = vec!["Token | Symbol | Address | Balance | SOL value".to_owned()];
+        for acc in self.fee_accounts.iter() {
+            let sol_value = acc
+                .sol_value
+                .map_or_else(|| "-".to_owned(), |v| v.to_string());
+            lines.push(match &acc.symbol {
+                Some(symbol) => format!(
+                    "{} | {} | {} | {} | {}",
+                    acc.mint, symbol, acc.address, acc.balance, sol_value
+                ),
+                None => format!(
+                    "[WARN] Unknown mint {} not in registry | {} | {}",
+                    acc.mint, acc.address, acc.balance
+                ),
+            });
+        }
+        let unvalued = self
+            .fee_accounts
+            .iter()
+            .filter(|acc| acc.sol_value.is_none())
+            .count();
+        lines.push(match unvalued {
+            0 => format!("Total SOL value: {}", self.total_sol_value),
+            n => format!(
+                "Total SOL value: {} (excluding {} accs without an exchange rate)",
+                self.total_sol_value, n
+            ),
+        });
+        lines.join("\n")
+    }
+
+    fn csv_rows(&self) -> Vec<&FeeAcc> {
+        self.fee_accounts.iter().collect()
+    }
+}
+
+/// Where a token account stands as a stakedex fee token account
+#[derive(Debug, PartialEq, Eq)]
+enum FeeAccStatus {
+    /// Fee token account of a mint in the mint registry
+    Registered,
+
+    /// Fee token account of a mint not in the mint registry
+    Unregistered,
+
+    /// Not a fee token account, with the reason
+    Invalid(String),
+}
+
+/// A fee token account is the `find_fee_token_acc` PDA of its mint and is its own token authority,
+/// so that stakedex can sign for it without a separate authority account
+fn fee_acc_status(
+    registry: &MintRegistry,
+    address: &Pubkey,
+    token_acc: &TokenAccount,
+) -> FeeAccStatus {
+    let pda = find_fee_token_acc(&token_acc.mint).0;
+    if *address != pda {
+        return FeeAccStatus::Invalid(format!(
+            "{} of mint {} is not the fee token acc PDA {}",
+            address, token_acc.mint, pda
+        ));
+    }
+    if token_acc.owner != pda {
+        return FeeAccStatus::Invalid(format!(
+            "{} is owned by {}, not by itself",
+            address, token_acc.owner
+        ));
+    }
+    match registry.get(&token_acc.mint) {
+        Some(_) => FeeAccStatus::Registered,
+        None => FeeAccStatus::Unregistered,
+    }
+}
+
+/// Offset of the owner field in a token account, right after the mint
+const TOKEN_ACCOUNT_OWNER_OFFSET: usize = 32;
+
+/// getProgramAccounts filters matching every token account of a token program:
+/// spl-token accounts are exactly TokenAccount::LEN long, Token-2022 accounts are either that long
+/// or have extensions, marked by AccountType::Account right after the base account
+fn token_account_filters(token_program: &Pubkey) -> Vec<Vec<RpcFilterType>> {
+    let base = vec![RpcFilterType::DataSize(
+        TokenAccount::LEN.try_into().unwrap(),
+    )];
+    match *token_program == spl_token_2022::ID {
+        true => vec![
+            base,
+            vec![RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+                TokenAccount::LEN,
+                vec![AccountType::Account as u8],
+            ))],
+        ],
+        false => vec![base],
+    }
+}
+
+/// Whether the leading mint and owner fields of a token account's `data` make `address` a fee token account:
+/// owned by itself and the `find_fee_token_acc` PDA of its mint
+fn is_self_owned_fee_acc(address: &Pubkey, data: &[u8]) -> bool {
+    let (mint, owner) = match (
+        data.get(..TOKEN_ACCOUNT_OWNER_OFFSET),
+        data.get(TOKEN_ACCOUNT_OWNER_OFFSET..TOKEN_ACCOUNT_OWNER_OFFSET + 32),
+    ) {
+        (Some(mint), Some(owner)) => (mint, owner),
+        _ => return false,
+    };
+    // checked first since deriving the PDA is comparatively expensive
+    if owner != address.as_ref() {
+        return false;
+    }
+    find_fee_token_acc(&Pubkey::try_from(mint).unwrap()).0 == *address
+}
+
+/// Scans all token accounts of spl-token and Token-2022 for fee token accounts,
+/// returning those of mints not in `registry`.
+///
+/// Fee token accounts are their own owner, which a memcmp filter can't express,
+/// so only the mint and owner fields of every token account are fetched and checked here.
+fn discover(args: &crate::Args, client: &RpcClient, registry: &MintRegistry) -> Vec<FeeAcc> {
+    let mut scanned = 0;
+    let mut addrs = Vec::new();
+    for token_program in [spl_token::ID, spl_token_2022::ID] {
+        for filters in token_account_filters(&token_program) {
+            let accounts = client
+                .get_program_accounts_with_config(
+                    &token_program,
+                    RpcProgramAccountsConfig {
+                        filters: Some(filters),
+                        account_config: RpcAccountInfoConfig {
+                            encoding: Some(UiAccountEncoding::Base64),
+                            data_slice: Some(UiDataSliceConfig {
+                                offset: 0,
+                                length: TOKEN_ACCOUNT_OWNER_OFFSET + 32,
+                            }),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    },
+                )
+                .unwrap();
+            scanned += accounts.len();
+            addrs.extend(
+                accounts
+                    .into_iter()
+                    .filter(|(addr, acc)| is_self_owned_fee_acc(addr, &acc.data))
+                    .map(|(addr, _)| addr),
             );
         }
     }
+    args.output.log(format!(
+        "Scanned {} token accs, {} are fee token accs",
+        scanned,
+        addrs.len()
+    ));
+
+    let fetched = get_multiple_accounts_chunked(client, &addrs).unwrap();
+    let mut unknown = Vec::new();
+    for (addr, opt) in addrs.into_iter().zip(fetched) {
+        // closed since the scan
+        let acc = match opt {
+            Some(acc) if is_token_program(&acc.owner) => acc,
+            _ => continue,
+        };
+        let parsed = match unpack_token_account(&acc.data) {
+            Ok(p) => p,
+            Err(_) => continue,
+        };
+        match fee_acc_status(registry, &addr, &parsed) {
+            FeeAccStatus::Unregistered => unknown.push((addr, parsed)),
+            FeeAccStatus::Registered => {}
+            FeeAccStatus::Invalid(e) => args.output.log(format!("[WARN] {}", e)),
+        }
+    }
+
+    let mints: Vec<_> = unknown.iter().map(|(_, parsed)| parsed.mint).collect();
+    let decimals = fetch_mint_decimals(client, &mints).unwrap();
+    unknown
+        .into_iter()
+        .zip(decimals)
+        .filter_map(|((addr, parsed), opt)| match opt {
+            Some(d) => Some(FeeAcc::new(
+                &parsed.mint,
+                None,
+                &addr,
+                parsed.amount,
+                d,
+                None,
+            )),
+            None => {
+                args.output.log(format!(
+                    "[WARN] Could not fetch decimals of unknown mint {} of fee acc {}",
+                    parsed.mint, addr
+                ));
+                None
+            }
+        })
+        .collect()
 }
 
-pub static ALL_XSOL_MINTS: [Pubkey; 12] = [
-    bsol::ID,
-    cogentsol::ID,
-    daosol::ID,
-    esol::ID,
-    jitosol::ID,
-    jsol::ID,
-    lainesol::ID,
-    msol::ID,
-    risksol::ID,
-    scnsol::ID,
-    spl_token::native_mint::ID,
-    stsol::ID,
-];
+#[cfg(test)]
+mod tests {
+    use stakedex_sdk_common::jitosol;
+
+    use super::*;
+
+    fn fee_acc(mint: &Pubkey) -> (Pubkey, TokenAccount) {
+        let address = find_fee_token_acc(mint).0;
+        let token_acc = TokenAccount {
+            mint: *mint,
+            owner: address,
+            amount: 1,
+            state: spl_token_2022::state::AccountState::Initialized,
+            ..Default::default()
+        };
+        (address, token_acc)
+    }
+
+    #[test]
+    fn test_fee_acc_status() {
+        let registry = MintRegistry::from_toml(
+            r#"
+[[mints]]
+mint = "J1toso1uCk3RLmjorhTtrVwY9HJ7X8V9yYac6Y7kGCPn"
+symbol = "jitoSOL"
+decimals = 9
+"#,
+        )
+        .unwrap();
+        let (address, token_acc) = fee_acc(&jitosol::ID);
+        assert_eq!(
+            fee_acc_status(&registry, &address, &token_acc),
+            FeeAccStatus::Registered
+        );
+
+        let (address, token_acc) = fee_acc(&Pubkey::new_unique());
+        assert_eq!(
+            fee_acc_status(&registry, &address, &token_acc),
+            FeeAccStatus::Unregistered
+        );
+        // not the PDA of its mint
+        assert!(matches!(
+            fee_acc_status(&registry, &Pubkey::new_unique(), &token_acc),
+            FeeAccStatus::Invalid(_)
+        ));
+        // PDA but not its own authority
+        let not_self_owned = TokenAccount {
+            owner: Pubkey::new_unique(),
+            ..token_acc
+        };
+        assert!(matches!(
+            fee_acc_status(&registry, &address, &not_self_owned),
+            FeeAccStatus::Invalid(_)
+        ));
+    }
+
+    #[test]
+    fn test_total_sol_value() {
+        let rate = ExchangeRate {
+            lamports: 11,
+            pool_tokens: 10,
+        };
+        let output = ListFeeAccsOutput::new(vec![
+            FeeAcc::new(
+                &jitosol::ID,
+                Some("jitoSOL".into()),
+                &Pubkey::new_unique(),
+                1_500_000_000,
+                9,
+                Some(&rate),
+            ),
+            FeeAcc::new(
+                &Pubkey::new_unique(),
+                None,
+                &Pubkey::new_unique(),
+                25,
+                1,
+                None,
+            ),
+        ]);
+        // unvalued accs are excluded from the total
+        assert_eq!(output.total_sol_value, 1.65);
+        assert!(output
+            .text()
+            .ends_with("Total SOL value: 1.65 (excluding 1 accs without an exchange rate)"));
+        assert_eq!(ListFeeAccsOutput::new(vec![]).total_sol_value, 0.0);
+    }
+
+    #[test]
+    fn test_is_self_owned_fee_acc() {
+        let (address, token_acc) = fee_acc(&Pubkey::new_unique());
+        let mut data = vec![0; TokenAccount::LEN];
+        TokenAccount::pack(token_acc, &mut data).unwrap();
+        assert!(is_self_owned_fee_acc(&address, &data));
+        // the scan only fetches the mint and owner fields
+        assert!(is_self_owned_fee_acc(
+            &address,
+            &data[..TOKEN_ACCOUNT_OWNER_OFFSET + 32]
+        ));
+        assert!(!is_self_owned_fee_acc(
+            &address,
+            &data[..TOKEN_ACCOUNT_OWNER_OFFSET]
+        ));
+
+        // self-owned but not the PDA of its mint
+        let other = Pubkey::new_unique();
+        let mut not_pda = data.clone();
+        not_pda[TOKEN_ACCOUNT_OWNER_OFFSET..TOKEN_ACCOUNT_OWNER_OFFSET + 32]
+            .copy_from_slice(other.as_ref());
+        assert!(!is_self_owned_fee_acc(&other, &not_pda));
+
+        // PDA but not its own owner
+        let not_self_owned = TokenAccount {
+            owner: Pubkey::new_unique(),
+            ..token_acc
+        };
+        TokenAccount::pack(not_self_owned, &mut data).unwrap();
+        assert!(!is_self_owned_fee_acc(&address, &data));
+    }
+
+    #[test]
+    fn test_token_account_filters() {
+        assert_eq!(token_account_filters(&spl_token::ID).len(), 1);
+        assert_eq!(token_account_filters(&spl_token_2022::ID).len(), 2);
+    }
+}
@@ -0,0 +1,125 @@
+use std::process;
+
+use clap::Args;
+use itertools::izip;
+
+use crate::{
+    pool_utils::exchange_rate_of,
+    token_utils::{get_multiple_accounts_chunked, is_token_program, unpack_mint},
+};
+
+use super::SubcmdExec;
+
+#[derive(Args, Debug)]
+#[command(
+    long_about = "List the mint registry: the bundled mints, extended by --mints-file. With --validate, also check the registry against on-chain state."
+)]
+pub struct MintsArgs {
+    #[arg(
+        long,
+        help = "Check that every mint exists with the registered decimals and token program and every pool is registered with its pool program, owned by it and has a readable exchange rate. Exits with a non-zero code if any check fails.",
+        default_value_t = false
+    )]
+    pub validate: bool,
+}
+
+impl SubcmdExec for MintsArgs {
+    fn process_cmd(&self, args: &crate::Args) {
+        let registry = args.mint_registry();
+        println!("Symbol | Mint | Decimals | Pool program | Pool");
+        for info in registry.mints.iter() {
+            println!(
+                "{} | {} | {} | {} | {}",
+                info.symbol,
+                info.mint,
+                info.decimals,
+                info.pool_program
+                    .map_or_else(|| "-".into(), |p| p.to_string()),
+                info.pool.map_or_else(|| "-".into(), |p| p.to_string()),
+            );
+        }
+        if !self.validate {
+            return;
+        }
+
+        let client = args.config.rpc_client();
+        let fetched_mints = get_multiple_accounts_chunked(&client, &registry.mints()).unwrap();
+        let pools: Vec<_> = registry
+            .mints
+            .iter()
+            .map(|info| info.pool.unwrap_or_default())
+            .collect();
+        let fetched_pools = get_multiple_accounts_chunked(&client, &pools).unwrap();
+        let mut ok = true;
+        for (info, mint_opt, pool_opt) in izip!(&registry.mints, fetched_mints, fetched_pools) {
+            let mint_acc = match mint_opt {
+                Some(a) => a,
+                None => {
+                    println!("[FAIL] {}: mint {} does not exist", info.symbol, info.mint);
+                    ok = false;
+                    continue;
+                }
+            };
+            if !is_token_program(&mint_acc.owner) {
+                println!(
+                    "[FAIL] {}: mint {} owned by {}, not a token program",
+                    info.symbol, info.mint, mint_acc.owner
+                );
+                ok = false;
+                continue;
+            }
+            if mint_acc.owner != info.token_program {
+                println!(
+                    "[FAIL] {}: registered with token program {}, mint owned by {}",
+                    info.symbol, info.token_program, mint_acc.owner
+                );
+                ok = false;
+            }
+            match unpack_mint(&mint_acc.data) {
+                Ok(mint) if mint.decimals == info.decimals => (),
+                Ok(mint) => {
+                    println!(
+                        "[FAIL] {}: registered with {} decimals, mint has {}",
+                        info.symbol, info.decimals, mint.decimals
+                    );
+                    ok = false;
+                }
+                Err(e) => {
+                    println!("[FAIL] {}: invalid mint account: {}", info.symbol, e);
+                    ok = false;
+                }
+            }
+            match (info.registered_pool(), pool_opt) {
+                (Err(e), _) => {
+                    println!("[FAIL] {}", e);
+                    ok = false;
+                }
+                (Ok(None), _) => (),
+                (Ok(Some((_, pool))), None) => {
+                    println!("[FAIL] {}: pool {} does not exist", info.symbol, pool);
+                    ok = false;
+                }
+                (Ok(Some((pool_program, pool))), Some(acc)) if acc.owner != pool_program => {
+                    println!(
+                        "[FAIL] {}: pool {} owned by {}, expected {}",
+                        info.symbol, pool, acc.owner, pool_program
+                    );
+                    ok = false;
+                }
+                (Ok(Some((pool_program, pool))), Some(acc)) => {
+                    if let Err(e) = exchange_rate_of(&pool_program, &acc.data) {
+                        println!(
+                            "[FAIL] {}: cannot read exchange rate of pool {}: {}",
+                            info.symbol, pool, e
+                        );
+                        ok = false;
+                    }
+                }
+            }
+        }
+        if !ok {
+            process::exit(1);
+        }
+        println!("All {} mints valid", registry.mints.len());
+    }
+}
@@ -1,25 +1,49 @@
 use clap::Subcommand;
 
+mod admin;
 mod create_fee_acc;
+mod decode_tx;
+mod deposit_stake;
+mod doctor;
 mod fund_sol_bridge;
+mod gov;
 mod index;
 mod list_fee_accs;
+mod mints;
+mod quote;
+mod swap;
 mod view_fee_acc;
 mod withdraw_fees;
 
+pub use admin::*;
 pub use create_fee_acc::*;
+pub use decode_tx::*;
+pub use deposit_stake::*;
+pub use doctor::*;
 pub use fund_sol_bridge::*;
+pub use gov::*;
 pub use index::*;
 pub use list_fee_accs::*;
+pub use mints::*;
+pub use quote::*;
+pub use swap::*;
 pub use view_fee_acc::*;
 pub use withdraw_fees::*;
 
 #[derive(Debug, Subcommand)]
 pub enum Subcmd {
+    Admin(AdminArgs),
     CreateFeeAcc(CreateFeeAccArgs),
+    DecodeTx(DecodeTxArgs),
+    DepositStake(DepositStakeArgs),
+    Doctor(DoctorArgs),
     FundSolBridge(FundSolBridgeArgs),
+    Gov(GovArgs),
     Index(IndexArgs),
     ListFeeAccs(ListFeeAccsArgs),
+    Mints(MintsArgs),
+    Quote(QuoteArgs),
+    Swap(SwapArgs),
     WithdrawFees(WithdrawFeesArgs),
     ViewFeeAcc(ViewFeeAccArgs),
 }
@@ -31,12 +55,442 @@ pub trait SubcmdExec {
 impl SubcmdExec for Subcmd {
     fn process_cmd(&self, args: &crate::Args) {
         match self {
+            Self::Admin(a) => a.process_cmd(args),
             Self::CreateFeeAcc(a) => a.process_cmd(args),
+            Self::DecodeTx(a) => a.process_cmd(args),
+            Self::DepositStake(a) => a.process_cmd(args),
+            Self::Doctor(a) => a.process_cmd(args),
             Self::FundSolBridge(a) => a.process_cmd(args),
+            Self::Gov(a) => a.process_cmd(args),
             Self::ListFeeAccs(a) => a.process_cmd(args),
+            Self::Mints(a) => a.process_cmd(args),
+            Self::Quote(a) => a.process_cmd(args),
+            Self::Swap(a) => a.process_cmd(args),
             Self::WithdrawFees(a) => a.process_cmd(args),
             Self::Index(a) => a.process_cmd(args),
             Self::ViewFeeAcc(a) => a.process_cmd(args),
         }
     }
 }
+
+/// Field names of every subcommand's output in one place, since they are part of the CLI's interface.
+/// Behavior is tested in each subcommand's module.
+#[cfg(test)]
+mod tests {
+    use solana_sdk::pubkey::Pubkey;
+
+    use stakedex_sdk_common::{jitosol, msol};
+
+    use crate::{
+        mint_registry::MintRegistry,
+        output::{field_names, render, OutputFormat},
+        pool_utils::ExchangeRate,
+        stakedex_utils::{Route, RouteQuote},
+        subcmd::index::db::summary::IndexSummary,
+    };
+
+    use super::*;
+
+    fn fee_acc() -> FeeAcc {
+        FeeAcc::new(
+            &Pubkey::new_unique(),
+            None,
+            &Pubkey::new_unique(),
+            1,
+            9,
+            None,
+        )
+    }
+
+    #[test]
+    fn test_output_field_names() {
+        const FEE_ACC_CSV: &str = "mint,symbol,address,amount,decimals,balance,sol_value";
+        assert_eq!(
+            field_names(&ListFeeAccsOutput::new(vec![fee_acc()])),
+            ["fee_accounts,total_sol_value", FEE_ACC_CSV]
+        );
+        assert_eq!(
+            field_names(&fee_acc()),
+            [
+                "address,amount,balance,decimals,mint,sol_value,symbol",
+                FEE_ACC_CSV
+            ]
+        );
+        assert_eq!(
+            field_names(&CreateFeeAccOutput {
+                fee_accounts: vec![FeeAccCreation::new(&Pubkey::new_unique(), false)],
+                signatures: vec![],
+            }),
+            ["fee_accounts,signatures", "mint,address,already_exists"]
+        );
+        assert_eq!(
+            field_names(&FundSolBridgeOutput {
+                sol_bridge_out: Pubkey::new_unique().to_string(),
+                existing_lamports: None,
+                required_lamports: 1,
+                transfer_lamports: 1,
+                signature: None,
+            }),
+            [
+                "existing_lamports,required_lamports,signature,sol_bridge_out,transfer_lamports",
+                "sol_bridge_out,existing_lamports,required_lamports,transfer_lamports,signature"
+            ]
+        );
+        assert_eq!(
+            field_names(&WithdrawFeesOutput {
+                withdrawals: vec![WithdrawFeesInstruction {
+                    mint: "mint".into(),
+                    destination: "dst".into(),
+                    token_program: "tp".into(),
+                    instruction: "AAEC".into(),
+                }],
+                created_token_accounts: vec![],
+                signatures: vec![],
+                proposal: None,
+            }),
+            [
+                "created_token_accounts,proposal,signatures,withdrawals",
+                "mint,destination,token_program,instruction"
+            ]
+        );
+        assert_eq!(
+            field_names(&AdminOutput {
+                summary: "summary".into(),
+                admin: "admin".into(),
+                instruction: "AAEC".into(),
+                proposal: None,
+            }),
+            [
+                "admin,instruction,proposal,summary",
+                "summary,admin,instruction,proposal"
+            ]
+        );
+        assert_eq!(
+            field_names(&IndexSummary {
+                invocations: 0,
+                transactions: 0,
+                decode_errors: 0,
+                archived_transactions: 0,
+                earliest_slot: None,
+                latest_slot: None,
+                mints_missing_decimals: 0,
+            }),
+            [
+                "archived_transactions,decode_errors,earliest_slot,invocations,latest_slot,mints_missing_decimals,transactions",
+                "invocations,transactions,decode_errors,archived_transactions,earliest_slot,latest_slot,mints_missing_decimals"
+            ]
+        );
+        assert_eq!(
+            field_names(&DoctorOutput {
+                checks: vec![Check {
+                    name: "program".into(),
+                    address: Pubkey::new_unique().to_string(),
+                    status: CheckStatus::Ok,
+                    message: "deployed".into(),
+                }],
+            }),
+            ["checks", "name,address,status,message"]
+        );
+        let mint = Pubkey::new_unique();
+        assert_eq!(
+            field_names(&QuoteOutput::new(
+                &MintRegistry::default(),
+                (&mint, 9),
+                (&mint, 9),
+                1,
+                &[(Route::SwapViaStake, Err("no pool".to_owned()))],
+            )),
+            [
+                "best_route,in_amount,in_balance,mint_in,mint_out,routes,symbol_in,symbol_out",
+                "route,out_amount,out_balance,fee_amount,fee_mint,error"
+            ]
+        );
+        assert_eq!(
+            field_names(&SwapOutput {
+                route: Route::SwapViaStake,
+                mint_in: mint.to_string(),
+                mint_out: mint.to_string(),
+                in_amount: 1,
+                quoted_out_amount: 1,
+                min_out_amount: 1,
+                simulated_out_amount: None,
+                destination: mint.to_string(),
+                signature: None,
+            }),
+            [
+                "destination,in_amount,min_out_amount,mint_in,mint_out,quoted_out_amount,route,signature,simulated_out_amount",
+                "route,mint_in,mint_out,in_amount,quoted_out_amount,min_out_amount,simulated_out_amount,destination,signature"
+            ]
+        );
+        assert_eq!(
+            field_names(&DepositStakeOutput {
+                stake_account: mint.to_string(),
+                voter: mint.to_string(),
+                lamports: 1,
+                staked_lamports: 1,
+                mint_out: mint.to_string(),
+                out_amount: 1,
+                out_balance: 1.0,
+                fee_amount: 0,
+                destination: mint.to_string(),
+                signature: None,
+            }),
+            [
+                "destination,fee_amount,lamports,mint_out,out_amount,out_balance,signature,stake_account,staked_lamports,voter",
+                "stake_account,voter,lamports,staked_lamports,mint_out,out_amount,out_balance,fee_amount,destination,signature"
+            ]
+        );
+    }
+
+    const A: &str = "4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi";
+    const B: &str = "8qbHbw2BbbTHBW1sbeqakYXVKRQM8Ne7pLK7m6CVfeR";
+    const C: &str = "CktRuQ2mttgRGkXJtyksdKHjUdc2C4TgDzyB98oEzy8";
+    const SIG: &str =
+        "5f5r5AjuFd8WwUagQSztAgufUCE6rdYhXmjU5rtnBPsxmfC5fFCUGiqQCcQZmAfFzuo6gyYYm616Roc1HEhREX5";
+
+    /// Asserts the full `--output json-compact` and `--output csv` renderings of `output`,
+    /// and that `--output json` is the same document pretty-printed
+    fn assert_rendered<T: CmdOutput>(output: &T, json: &str, csv: &str) {
+        assert_eq!(render(OutputFormat::JsonCompact, output), json);
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&render(OutputFormat::Json, output)).unwrap(),
+            serde_json::from_str::<serde_json::Value>(json).unwrap()
+        );
+        assert_eq!(render(OutputFormat::Csv, output), csv);
+    }
+
+    fn fixed_fee_accs() -> Vec<FeeAcc> {
+        let rate = ExchangeRate {
+            lamports: 11,
+            pool_tokens: 10,
+        };
+        vec![
+            FeeAcc::new(
+                &jitosol::ID,
+                Some("jitoSOL".into()),
+                &Pubkey::new_from_array([1; 32]),
+                1_500_000_000,
+                9,
+                Some(&rate),
+            ),
+            FeeAcc::new(
+                &Pubkey::new_from_array([2; 32]),
+                None,
+                &Pubkey::new_from_array([3; 32]),
+                25,
+                1,
+                None,
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_render_fee_accs() {
+        assert_rendered(
+            &ListFeeAccsOutput::new(fixed_fee_accs()),
+            r#"{"fee_accounts":[{"mint":"J1toso1uCk3RLmjorhTtrVwY9HJ7X8V9yYac6Y7kGCPn","symbol":"jitoSOL","address":"4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi","amount":1500000000,"decimals":9,"balance":1.5,"sol_value":1.65},{"mint":"8qbHbw2BbbTHBW1sbeqakYXVKRQM8Ne7pLK7m6CVfeR","symbol":null,"address":"CktRuQ2mttgRGkXJtyksdKHjUdc2C4TgDzyB98oEzy8","amount":25,"decimals":1,"balance":2.5,"sol_value":null}],"total_sol_value":1.65}"#,
+            "mint,symbol,address,amount,decimals,balance,sol_value\n\
+             J1toso1uCk3RLmjorhTtrVwY9HJ7X8V9yYac6Y7kGCPn,jitoSOL,4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi,1500000000,9,1.5,1.65\n\
+             8qbHbw2BbbTHBW1sbeqakYXVKRQM8Ne7pLK7m6CVfeR,,CktRuQ2mttgRGkXJtyksdKHjUdc2C4TgDzyB98oEzy8,25,1,2.5,",
+        );
+        assert_rendered(
+            &fixed_fee_accs().remove(0),
+            r#"{"mint":"J1toso1uCk3RLmjorhTtrVwY9HJ7X8V9yYac6Y7kGCPn","symbol":"jitoSOL","address":"4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi","amount":1500000000,"decimals":9,"balance":1.5,"sol_value":1.65}"#,
+            "mint,symbol,address,amount,decimals,balance,sol_value\n\
+             J1toso1uCk3RLmjorhTtrVwY9HJ7X8V9yYac6Y7kGCPn,jitoSOL,4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi,1500000000,9,1.5,1.65",
+        );
+    }
+
+    #[test]
+    fn test_render_create_fee_acc() {
+        assert_rendered(
+            &CreateFeeAccOutput {
+                fee_accounts: vec![
+                    FeeAccCreation {
+                        mint: A.into(),
+                        address: B.into(),
+                        already_exists: true,
+                    },
+                    FeeAccCreation {
+                        mint: C.into(),
+                        address: A.into(),
+                        already_exists: false,
+                    },
+                ],
+                signatures: vec![SIG.into()],
+            },
+            r#"{"fee_accounts":[{"mint":"4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi","address":"8qbHbw2BbbTHBW1sbeqakYXVKRQM8Ne7pLK7m6CVfeR","already_exists":true},{"mint":"CktRuQ2mttgRGkXJtyksdKHjUdc2C4TgDzyB98oEzy8","address":"4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi","already_exists":false}],"signatures":["5f5r5AjuFd8WwUagQSztAgufUCE6rdYhXmjU5rtnBPsxmfC5fFCUGiqQCcQZmAfFzuo6gyYYm616Roc1HEhREX5"]}"#,
+            "mint,address,already_exists\n\
+             4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi,8qbHbw2BbbTHBW1sbeqakYXVKRQM8Ne7pLK7m6CVfeR,true\n\
+             CktRuQ2mttgRGkXJtyksdKHjUdc2C4TgDzyB98oEzy8,4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi,false",
+        );
+    }
+
+    #[test]
+    fn test_render_fund_sol_bridge() {
+        assert_rendered(
+            &FundSolBridgeOutput {
+                sol_bridge_out: A.into(),
+                existing_lamports: Some(1_000_000),
+                required_lamports: 2_039_280,
+                transfer_lamports: 1_039_280,
+                signature: Some(SIG.into()),
+            },
+            r#"{"sol_bridge_out":"4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi","existing_lamports":1000000,"required_lamports":2039280,"transfer_lamports":1039280,"signature":"5f5r5AjuFd8WwUagQSztAgufUCE6rdYhXmjU5rtnBPsxmfC5fFCUGiqQCcQZmAfFzuo6gyYYm616Roc1HEhREX5"}"#,
+            "sol_bridge_out,existing_lamports,required_lamports,transfer_lamports,signature\n\
+             4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi,1000000,2039280,1039280,5f5r5AjuFd8WwUagQSztAgufUCE6rdYhXmjU5rtnBPsxmfC5fFCUGiqQCcQZmAfFzuo6gyYYm616Roc1HEhREX5",
+        );
+    }
+
+    #[test]
+    fn test_render_withdraw_fees() {
+        assert_rendered(
+            &WithdrawFeesOutput {
+                withdrawals: vec![WithdrawFeesInstruction {
+                    mint: A.into(),
+                    destination: B.into(),
+                    token_program: spl_token::ID.to_string(),
+                    instruction: "AAEC".into(),
+                }],
+                created_token_accounts: vec![B.into()],
+                signatures: vec![SIG.into()],
+                proposal: Some(C.into()),
+            },
+            r#"{"withdrawals":[{"mint":"4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi","destination":"8qbHbw2BbbTHBW1sbeqakYXVKRQM8Ne7pLK7m6CVfeR","token_program":"TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA","instruction":"AAEC"}],"created_token_accounts":["8qbHbw2BbbTHBW1sbeqakYXVKRQM8Ne7pLK7m6CVfeR"],"signatures":["5f5r5AjuFd8WwUagQSztAgufUCE6rdYhXmjU5rtnBPsxmfC5fFCUGiqQCcQZmAfFzuo6gyYYm616Roc1HEhREX5"],"proposal":"CktRuQ2mttgRGkXJtyksdKHjUdc2C4TgDzyB98oEzy8"}"#,
+            "mint,destination,token_program,instruction\n\
+             4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi,8qbHbw2BbbTHBW1sbeqakYXVKRQM8Ne7pLK7m6CVfeR,TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA,AAEC",
+        );
+    }
+
+    #[test]
+    fn test_render_admin() {
+        assert_rendered(
+            &AdminOutput {
+                summary: "CloseFeeTokenAccount mint: jitoSOL, close to: admin".into(),
+                admin: A.into(),
+                instruction: "AAEC".into(),
+                proposal: None,
+            },
+            r#"{"summary":"CloseFeeTokenAccount mint: jitoSOL, close to: admin","admin":"4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi","instruction":"AAEC","proposal":null}"#,
+            "summary,admin,instruction,proposal\n\
+             \"CloseFeeTokenAccount mint: jitoSOL, close to: admin\",4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi,AAEC,",
+        );
+    }
+
+    #[test]
+    fn test_render_index_summary() {
+        assert_rendered(
+            &IndexSummary {
+                invocations: 10,
+                transactions: 8,
+                decode_errors: 1,
+                archived_transactions: 2,
+                earliest_slot: Some(200_000_000),
+                latest_slot: Some(250_000_000),
+                mints_missing_decimals: 0,
+            },
+            r#"{"invocations":10,"transactions":8,"decode_errors":1,"archived_transactions":2,"earliest_slot":200000000,"latest_slot":250000000,"mints_missing_decimals":0}"#,
+            "invocations,transactions,decode_errors,archived_transactions,earliest_slot,latest_slot,mints_missing_decimals\n\
+             10,8,1,2,200000000,250000000,0",
+        );
+    }
+
+    #[test]
+    fn test_render_doctor() {
+        assert_rendered(
+            &DoctorOutput {
+                checks: vec![
+                    Check {
+                        name: "program".into(),
+                        address: A.into(),
+                        status: CheckStatus::Ok,
+                        message: "deployed".into(),
+                    },
+                    Check {
+                        name: "fee acc jitoSOL".into(),
+                        address: B.into(),
+                        status: CheckStatus::Fail,
+                        message: "missing".into(),
+                    },
+                ],
+            },
+            r#"{"checks":[{"name":"program","address":"4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi","status":"ok","message":"deployed"},{"name":"fee acc jitoSOL","address":"8qbHbw2BbbTHBW1sbeqakYXVKRQM8Ne7pLK7m6CVfeR","status":"fail","message":"missing"}]}"#,
+            "name,address,status,message\n\
+             program,4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi,ok,deployed\n\
+             fee acc jitoSOL,8qbHbw2BbbTHBW1sbeqakYXVKRQM8Ne7pLK7m6CVfeR,fail,missing",
+        );
+    }
+
+    #[test]
+    fn test_render_quote() {
+        let quote = RouteQuote {
+            route: Route::SwapViaStake,
+            in_amount: 1_000_000_000,
+            out_amount: 900_000_000,
+            fee_amount: 1_000_000,
+            fee_mint: msol::ID,
+        };
+        assert_rendered(
+            &QuoteOutput::new(
+                &MintRegistry::bundled(),
+                (&jitosol::ID, 9),
+                (&msol::ID, 9),
+                1_000_000_000,
+                &[
+                    (
+                        Route::StakeWrappedSol,
+                        Err("mint_in is not wSOL".to_owned()),
+                    ),
+                    (Route::SwapViaStake, Ok(quote)),
+                    (Route::DepositStake, Err("no vote account".to_owned())),
+                ],
+            ),
+            r#"{"mint_in":"J1toso1uCk3RLmjorhTtrVwY9HJ7X8V9yYac6Y7kGCPn","symbol_in":"jitoSOL","mint_out":"mSoLzYCxHdYgdzU16g5QSh3i5K3z3KZK7ytfqcJm7So","symbol_out":"mSOL","in_amount":1000000000,"in_balance":1.0,"routes":[{"route":"StakeWrappedSol","out_amount":null,"out_balance":null,"fee_amount":null,"fee_mint":null,"error":"mint_in is not wSOL"},{"route":"SwapViaStake","out_amount":900000000,"out_balance":0.9,"fee_amount":1000000,"fee_mint":"mSoLzYCxHdYgdzU16g5QSh3i5K3z3KZK7ytfqcJm7So","error":null},{"route":"DepositStake","out_amount":null,"out_balance":null,"fee_amount":null,"fee_mint":null,"error":"no vote account"}],"best_route":"SwapViaStake"}"#,
+            "route,out_amount,out_balance,fee_amount,fee_mint,error\n\
+             StakeWrappedSol,,,,,mint_in is not wSOL\n\
+             SwapViaStake,900000000,0.9,1000000,mSoLzYCxHdYgdzU16g5QSh3i5K3z3KZK7ytfqcJm7So,\n\
+             DepositStake,,,,,no vote account",
+        );
+    }
+
+    #[test]
+    fn test_render_swap() {
+        assert_rendered(
+            &SwapOutput {
+                route: Route::SwapViaStake,
+                mint_in: jitosol::ID.to_string(),
+                mint_out: msol::ID.to_string(),
+                in_amount: 1_000_000_000,
+                quoted_out_amount: 900_000_000,
+                min_out_amount: 895_500_000,
+                simulated_out_amount: Some(900_000_001),
+                destination: A.into(),
+                signature: None,
+            },
+            r#"{"route":"SwapViaStake","mint_in":"J1toso1uCk3RLmjorhTtrVwY9HJ7X8V9yYac6Y7kGCPn","mint_out":"mSoLzYCxHdYgdzU16g5QSh3i5K3z3KZK7ytfqcJm7So","in_amount":1000000000,"quoted_out_amount":900000000,"min_out_amount":895500000,"simulated_out_amount":900000001,"destination":"4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi","signature":null}"#,
+            "route,mint_in,mint_out,in_amount,quoted_out_amount,min_out_amount,simulated_out_amount,destination,signature\n\
+             SwapViaStake,J1toso1uCk3RLmjorhTtrVwY9HJ7X8V9yYac6Y7kGCPn,mSoLzYCxHdYgdzU16g5QSh3i5K3z3KZK7ytfqcJm7So,1000000000,900000000,895500000,900000001,4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi,",
+        );
+    }
+
+    #[test]
+    fn test_render_deposit_stake() {
+        assert_rendered(
+            &DepositStakeOutput {
+                stake_account: A.into(),
+                voter: B.into(),
+                lamports: 1_002_282_880,
+                staked_lamports: 1_000_000_000,
+                mint_out: jitosol::ID.to_string(),
+                out_amount: 950_000_000,
+                out_balance: 0.95,
+                fee_amount: 0,
+                destination: C.into(),
+                signature: Some(SIG.into()),
+            },
+            r#"{"stake_account":"4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi","voter":"8qbHbw2BbbTHBW1sbeqakYXVKRQM8Ne7pLK7m6CVfeR","lamports":1002282880,"staked_lamports":1000000000,"mint_out":"J1toso1uCk3RLmjorhTtrVwY9HJ7X8V9yYac6Y7kGCPn","out_amount":950000000,"out_balance":0.95,"fee_amount":0,"destination":"CktRuQ2mttgRGkXJtyksdKHjUdc2C4TgDzyB98oEzy8","signature":"5f5r5AjuFd8WwUagQSztAgufUCE6rdYhXmjU5rtnBPsxmfC5fFCUGiqQCcQZmAfFzuo6gyYYm616Roc1HEhREX5"}"#,
+            "stake_account,voter,lamports,staked_lamports,mint_out,out_amount,out_balance,fee_amount,destination,signature\n\
+             4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi,8qbHbw2BbbTHBW1sbeqakYXVKRQM8Ne7pLK7m6CVfeR,1002282880,1000000000,J1toso1uCk3RLmjorhTtrVwY9HJ7X8V9yYac6Y7kGCPn,950000000,0.95,0,CktRuQ2mttgRGkXJtyksdKHjUdc2C4TgDzyB98oEzy8,5f5r5AjuFd8WwUagQSztAgufUCE6rdYhXmjU5rtnBPsxmfC5fFCUGiqQCcQZmAfFzuo6gyYYm616Roc1HEhREX5",
+        );
+    }
+}
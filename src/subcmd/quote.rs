@@ -0,0 +1,276 @@
+use std::process;
+
+use clap::Args;
+use serde::Serialize;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use spl_token::{amount_to_ui_amount, ui_amount_to_amount};
+
+use crate::{
+    mint_registry::MintRegistry,
+    output::{print_output, CmdOutput},
+    stakedex_utils::{best_quote, load_stakedex, quote_routes, Route, RouteQuote},
+    token_utils::fetch_mint_decimals,
+};
+
+use super::SubcmdExec;
+
+#[derive(Args, Debug)]
+#[command(
+    long_about = "Quote swapping one xSOL (or wSOL) for another through every stakedex route, showing why the others dont apply, and mark the best one"
+)]
+pub struct QuoteArgs {
+    #[arg(
+        help = "Pubkey of the mint to swap from. So11111111111111111111111111111111111111112 for SOL"
+    )]
+    pub mint_in: Pubkey,
+
+    #[arg(help = "Pubkey of the xSOL mint to swap to")]
+    pub mint_out: Pubkey,
+
+    #[arg(help = "Amount of mint_in to swap, in tokens, e.g. 1.5")]
+    pub amount: f64,
+
+    #[arg(
+        long,
+        help = "Also quote depositing a stake account of `amount` SOL delegated to this vote account (DepositStake route). Only with wSOL as mint_in, so that the routes' outputs are comparable"
+    )]
+    pub vote_account: Option<Pubkey>,
+}
+
+impl SubcmdExec for QuoteArgs {
+    fn process_cmd(&self, args: &crate::Args) {
+        let client = args.config.rpc_client();
+        if self.vote_account.is_some() && self.mint_in != spl_token::native_mint::ID {
+            args.output.log(format!(
+                "[FAIL] --vote-account quotes a stake account of `amount` SOL, so mint_in must be {}",
+                spl_token::native_mint::ID
+            ));
+            process::exit(1);
+        }
+        let registry = args.mint_registry();
+        let decimals = fetch_swap_mint_decimals(&client, &self.mint_in, &self.mint_out);
+        let amount = ui_amount_to_amount(self.amount, decimals[0]);
+
+        let stakedex = load_stakedex(args, &client).unwrap();
+        let quotes = quote_routes(
+            &stakedex,
+            &self.mint_in,
+            &self.mint_out,
+            amount,
+            self.vote_account.as_ref(),
+        );
+        let output = QuoteOutput::new(
+            &registry,
+            (&self.mint_in, decimals[0]),
+            (&self.mint_out, decimals[1]),
+            amount,
+            &quotes,
+        );
+        print_output(args.output, &output);
+    }
+}
+
+/// Decimals of (mint_in, mint_out). Panics if either is not a valid mint
+pub fn fetch_swap_mint_decimals(
+    client: &RpcClient,
+    mint_in: &Pubkey,
+    mint_out: &Pubkey,
+) -> [u8; 2] {
+    let mints = [*mint_in, *mint_out];
+    let fetched = fetch_mint_decimals(client, &mints).unwrap();
+    [0, 1].map(|i| {
+        fetched[i].unwrap_or_else(|| panic!("Could not fetch decimals of mint {}", mints[i]))
+    })
+}
+
+/// Quote of a single route. Only `error` is set if the route can't be taken
+#[derive(Debug, Serialize)]
+pub struct RouteQuoteRow {
+    pub route: Route,
+    pub out_amount: Option<u64>,
+    pub out_balance: Option<f64>,
+    pub fee_amount: Option<u64>,
+    pub fee_mint: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct QuoteOutput {
+    pub mint_in: String,
+    pub symbol_in: Option<String>,
+    pub mint_out: String,
+    pub symbol_out: Option<String>,
+    pub in_amount: u64,
+    pub in_balance: f64,
+    pub routes: Vec<RouteQuoteRow>,
+    pub best_route: Option<Route>,
+}
+
+impl QuoteOutput {
+    /// `mint_in` and `mint_out` are (mint, decimals)
+    pub fn new(
+        registry: &MintRegistry,
+        mint_in: (&Pubkey, u8),
+        mint_out: (&Pubkey, u8),
+        in_amount: u64,
+        quotes: &[(Route, Result<RouteQuote, String>)],
+    ) -> Self {
+        let symbol_of = |mint: &Pubkey| registry.get(mint).map(|info| info.symbol.clone());
+        let routes = quotes
+            .iter()
+            .map(|(route, res)| match res {
+                Ok(q) => RouteQuoteRow {
+                    route: *route,
+                    out_amount: Some(q.out_amount),
+                    out_balance: Some(amount_to_ui_amount(q.out_amount, mint_out.1)),
+                    fee_amount: Some(q.fee_amount),
+                    fee_mint: Some(q.fee_mint.to_string()),
+                    error: None,
+                },
+                Err(e) => RouteQuoteRow {
+                    route: *route,
+                    out_amount: None,
+                    out_balance: None,
+                    fee_amount: None,
+                    fee_mint: None,
+                    error: Some(e.clone()),
+                },
+            })
+            .collect();
+        Self {
+            mint_in: mint_in.0.to_string(),
+            symbol_in: symbol_of(mint_in.0),
+            mint_out: mint_out.0.to_string(),
+            symbol_out: symbol_of(mint_out.0),
+            in_amount,
+            in_balance: amount_to_ui_amount(in_amount, mint_in.1),
+            routes,
+            best_route: best_quote(quotes.iter().map(|(_, res)| res)).map(|q| q.route),
+        }
+    }
+}
+
+impl CmdOutput for QuoteOutput {
+    type Row = RouteQuoteRow;
+
+    fn text(&self) -> String {
+        let symbol_in = self.symbol_in.as_ref().unwrap_or(&self.mint_in);
+        let symbol_out = self.symbol_out.as_ref().unwrap_or(&self.mint_out);
+        let mut lines = vec![format!(
+            "{} {} -> {}",
+            self.in_balance, symbol_in, symbol_out
+        )];
+        for row in self.routes.iter() {
+            let best = match self.best_route == Some(row.route) {
+                true => " (best)",
+                false => "",
+            };
+            match (row.out_balance, row.fee_amount, &row.fee_mint) {
+                (Some(out_balance), Some(fee_amount), Some(fee_mint)) => lines.push(format!(
+                    "{:?}{}: {} {} | fee: {} atomics of {}",
+                    row.route, best, out_balance, symbol_out, fee_amount, fee_mint
+                )),
+                _ => lines.push(format!(
+                    "{:?}: unavailable: {}",
+                    row.route,
+                    row.error.as_deref().unwrap_or_default()
+                )),
+            }
+        }
+        if self.best_route.is_none() {
+            lines.push("No route available".into());
+        }
+        lines.join("\n")
+    }
+
+    fn csv_rows(&self) -> Vec<&RouteQuoteRow> {
+        self.routes.iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quote(route: Route, out_amount: u64, fee_mint: &Pubkey) -> RouteQuote {
+        RouteQuote {
+            route,
+            in_amount: 2_000_000_000,
+            out_amount,
+            fee_amount: 1_000_000,
+            fee_mint: *fee_mint,
+        }
+    }
+
+    #[test]
+    fn test_quote_output_new() {
+        let mint_in = spl_token::native_mint::ID;
+        let mint_out = Pubkey::new_unique();
+        let quotes = [
+            (
+                Route::StakeWrappedSol,
+                Ok(quote(Route::StakeWrappedSol, 1_900_000_000, &mint_out)),
+            ),
+            (
+                Route::DepositStake,
+                Err("pool does not accept stake delegated to x".to_owned()),
+            ),
+        ];
+        let output = QuoteOutput::new(
+            &MintRegistry::default(),
+            (&mint_in, 9),
+            (&mint_out, 9),
+            2_000_000_000,
+            &quotes,
+        );
+        assert_eq!(output.in_balance, 2.0);
+        assert_eq!(output.best_route, Some(Route::StakeWrappedSol));
+        assert_eq!(output.routes[0].out_balance, Some(1.9));
+        assert_eq!(output.routes[0].fee_mint, Some(mint_out.to_string()));
+        assert!(output.routes[0].error.is_none());
+        // failed routes only report their error
+        let failed = &output.routes[1];
+        assert_eq!(failed.route, Route::DepositStake);
+        assert!(failed.out_amount.is_none() && failed.fee_amount.is_none());
+        assert_eq!(
+            failed.error.as_deref(),
+            Some("pool does not accept stake delegated to x")
+        );
+    }
+
+    #[test]
+    fn test_quote_output_best_route() {
+        let mint_in = spl_token::native_mint::ID;
+        let mint_out = Pubkey::new_unique();
+        let quotes = [
+            (
+                Route::StakeWrappedSol,
+                Ok(quote(Route::StakeWrappedSol, 1_900_000_000, &mint_out)),
+            ),
+            (
+                Route::DepositStake,
+                Ok(quote(Route::DepositStake, 1_950_000_000, &mint_out)),
+            ),
+        ];
+        let output = QuoteOutput::new(
+            &MintRegistry::default(),
+            (&mint_in, 9),
+            (&mint_out, 9),
+            2_000_000_000,
+            &quotes,
+        );
+        assert_eq!(output.best_route, Some(Route::DepositStake));
+        assert!(output.text().contains("DepositStake (best): 1.95"));
+
+        let output = QuoteOutput::new(
+            &MintRegistry::default(),
+            (&mint_in, 9),
+            (&mint_out, 9),
+            2_000_000_000,
+            &[(Route::SwapViaStake, Err("no pool".to_owned()))],
+        );
+        assert!(output.best_route.is_none());
+        assert!(output.text().ends_with("No route available"));
+    }
+}
@@ -0,0 +1,386 @@
+use std::{
+    process,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use clap::Args;
+use jupiter_amm_interface::SwapParams;
+use serde::Serialize;
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{
+    rpc_client::RpcClient,
+    rpc_config::{RpcSimulateTransactionAccountsConfig, RpcSimulateTransactionConfig},
+};
+use solana_sdk::{
+    instruction::Instruction, pubkey::Pubkey, system_instruction, system_program,
+    transaction::Transaction,
+};
+use spl_associated_token_account::{
+    get_associated_token_address_with_program_id,
+    instruction::create_associated_token_account_idempotent,
+};
+use spl_token::ui_amount_to_amount;
+
+use crate::{
+    output::{print_output, CmdOutput, OutputFormat},
+    stakedex_utils::{load_stakedex, quote_swap, Route},
+    token_utils::{token_program_of, unpack_token_account},
+    tx_utils::{build_tx, send_or_sim_tx, TxSigners},
+};
+
+use super::{fetch_swap_mint_decimals, SubcmdExec};
+
+const MAX_SLIPPAGE_BPS: u16 = 10_000;
+
+#[derive(Args, Debug)]
+#[command(
+    long_about = "Swap one xSOL (or SOL) for another through stakedex. Uses StakeWrappedSol for SOL input, else SwapViaStake"
+)]
+pub struct SwapArgs {
+    #[arg(
+        help = "Pubkey of the mint to swap from. So11111111111111111111111111111111111111112 to swap SOL, which is wrapped for the swap"
+    )]
+    pub mint_in: Pubkey,
+
+    #[arg(help = "Pubkey of the xSOL mint to swap to")]
+    pub mint_out: Pubkey,
+
+    #[arg(help = "Amount of mint_in to swap, in tokens, e.g. 1.5")]
+    pub amount: f64,
+
+    #[arg(
+        long,
+        help = "Max shortfall from the quoted output to accept, in basis points. Checked by simulating the swap before sending it",
+        default_value_t = 50,
+        value_parser = clap::value_parser!(u16).range(0..=i64::from(MAX_SLIPPAGE_BPS))
+    )]
+    pub slippage_bps: u16,
+}
+
+impl SubcmdExec for SwapArgs {
+    fn process_cmd(&self, args: &crate::Args) {
+        let client = args.config.rpc_client();
+        let payer = args.config.signer(&args.offline);
+        let user = payer.pubkey();
+        let decimals = fetch_swap_mint_decimals(&client, &self.mint_in, &self.mint_out);
+        let amount = ui_amount_to_amount(self.amount, decimals[0]);
+
+        let stakedex = load_stakedex(args, &client).unwrap();
+        let (route, quote) = quote_swap(&stakedex, &self.mint_in, &self.mint_out, amount);
+        let quote = match quote {
+            Ok(q) => q,
+            Err(e) => {
+                args.output
+                    .log(format!("[FAIL] {:?} unavailable: {}", route, e));
+                process::exit(1);
+            }
+        };
+        let min_out_amount = min_out_amount(quote.out_amount, self.slippage_bps);
+        args.output.log(format!(
+            "{:?}: {} -> {} atomics, min {} atomics. Fee: {} atomics of {}",
+            route, amount, quote.out_amount, min_out_amount, quote.fee_amount, quote.fee_mint
+        ));
+
+        let is_sol_in = self.mint_in == spl_token::native_mint::ID;
+        let in_token_program = match is_sol_in {
+            true => spl_token::ID,
+            false => token_program_of(&client, &self.mint_in).unwrap(),
+        };
+        let out_token_program = token_program_of(&client, &self.mint_out).unwrap();
+        let source =
+            get_associated_token_address_with_program_id(&user, &self.mint_in, &in_token_program);
+        let destination =
+            get_associated_token_address_with_program_id(&user, &self.mint_out, &out_token_program);
+
+        let sol_in = is_sol_in.then(|| SolIn {
+            wsol_account: source,
+            lamports: amount,
+            created: client
+                .get_account_with_commitment(&source, client.commitment())
+                .unwrap()
+                .value
+                .is_none(),
+        });
+        let swap_params = SwapParams {
+            in_amount: amount,
+            source_mint: self.mint_in,
+            destination_mint: self.mint_out,
+            source_token_account: source,
+            destination_token_account: destination,
+            user_transfer_authority: user,
+            open_order_address: None,
+            quote_mint_to_referrer: None,
+        };
+        let swap_ix = match route {
+            Route::StakeWrappedSol => stakedex.stake_wrapped_sol_ix(&swap_params).unwrap(),
+            _ => stakedex
+                .swap_via_stake_ix(&swap_params, bridge_stake_seed())
+                .unwrap(),
+        };
+        let ixs = swap_ixs(
+            &user,
+            &self.mint_out,
+            &out_token_program,
+            sol_in.as_ref(),
+            swap_ix,
+        );
+
+        let signers = TxSigners::new(args, payer);
+        let tx = build_tx(args, &client, &ixs, &signers);
+        let mut output = SwapOutput {
+            route,
+            mint_in: self.mint_in.to_string(),
+            mint_out: self.mint_out.to_string(),
+            in_amount: amount,
+            quoted_out_amount: quote.out_amount,
+            min_out_amount,
+            simulated_out_amount: None,
+            destination: destination.to_string(),
+            signature: None,
+        };
+        // the tx isnt simulated if it is only printed for signing elsewhere
+        if args.offline.sign_only || args.offline.output_tx.is_some() {
+            args.output.log(format!(
+                "[WARN] --slippage-bps is not checked when signing offline, the transaction has no on-chain bound on its output (quoted min {} atomics)",
+                min_out_amount
+            ));
+        } else {
+            let simulated = simulate_out_amount(args.output, &client, &tx, &destination);
+            output.simulated_out_amount = Some(simulated);
+            if simulated < min_out_amount {
+                args.output.log(format!(
+                    "[FAIL] Simulated output {} is below min output {} (--slippage-bps {})",
+                    simulated, min_out_amount, self.slippage_bps
+                ));
+                process::exit(1);
+            }
+        }
+        output.signature = send_or_sim_tx(args, &client, &tx, &signers).map(|s| s.to_string());
+        print_output(args.output, &output);
+    }
+}
+
+/// `out_amount` less `slippage_bps`, rounded down
+pub fn min_out_amount(out_amount: u64, slippage_bps: u16) -> u64 {
+    let bps = u128::from(MAX_SLIPPAGE_BPS - slippage_bps.min(MAX_SLIPPAGE_BPS));
+    (u128::from(out_amount) * bps / u128::from(MAX_SLIPPAGE_BPS))
+        .try_into()
+        .unwrap()
+}
+
+/// SOL input, wrapped into `user`'s wSOL ATA for the swap
+struct SolIn {
+    wsol_account: Pubkey,
+    lamports: u64,
+
+    /// The wSOL ATA didnt exist before, so it is closed after the swap.
+    /// Else any existing wSOL balance is left wrapped
+    created: bool,
+}
+
+/// Instructions of a swap from `user`'s ATAs:
+/// create the mint_out ATA, wrap `sol_in` if any, swap, then unwrap if the wSOL ATA was created
+fn swap_ixs(
+    user: &Pubkey,
+    mint_out: &Pubkey,
+    out_token_program: &Pubkey,
+    sol_in: Option<&SolIn>,
+    swap_ix: Instruction,
+) -> Vec<Instruction> {
+    let mut ixs = vec![create_associated_token_account_idempotent(
+        user,
+        user,
+        mint_out,
+        out_token_program,
+    )];
+    if let Some(sol_in) = sol_in {
+        ixs.extend([
+            create_associated_token_account_idempotent(
+                user,
+                user,
+                &spl_token::native_mint::ID,
+                &spl_token::ID,
+            ),
+            system_instruction::transfer(user, &sol_in.wsol_account, sol_in.lamports),
+            spl_token::instruction::sync_native(&spl_token::ID, &sol_in.wsol_account).unwrap(),
+        ]);
+    }
+    ixs.push(swap_ix);
+    if let Some(sol_in) = sol_in.filter(|s| s.created) {
+        ixs.push(
+            spl_token::instruction::close_account(
+                &spl_token::ID,
+                &sol_in.wsol_account,
+                user,
+                user,
+                &[],
+            )
+            .unwrap(),
+        );
+    }
+    ixs
+}
+
+/// SwapViaStake creates a bridge stake account from this seed,
+/// so it must not collide with another of the user's in-flight swaps
+fn bridge_stake_seed() -> u32 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .subsec_nanos()
+}
+
+/// Amount `destination` receives from `tx`, by simulating it
+fn simulate_out_amount(
+    output: OutputFormat,
+    client: &RpcClient,
+    tx: &Transaction,
+    destination: &Pubkey,
+) -> u64 {
+    let balance_before = match client.get_account(destination) {
+        Ok(acc) => unpack_token_account(&acc.data).unwrap().amount,
+        Err(_) => 0,
+    };
+    let sim_result = client
+        .simulate_transaction_with_config(
+            tx,
+            RpcSimulateTransactionConfig {
+                sig_verify: false,
+                replace_recent_blockhash: true,
+                accounts: Some(RpcSimulateTransactionAccountsConfig {
+                    encoding: Some(UiAccountEncoding::Base64),
+                    addresses: vec![destination.to_string()],
+                }),
+                ..Default::default()
+            },
+        )
+        .unwrap()
+        .value;
+    if let Some(err) = sim_result.err {
+        output.log(format!("[FAIL] Swap simulation failed: {:?}", err));
+        for log in sim_result.logs.iter().flatten() {
+            output.log(format!("  {}", log));
+        }
+        process::exit(1);
+    }
+    let data = sim_result
+        .accounts
+        .and_then(|mut accs| accs.pop())
+        .flatten()
+        .and_then(|acc| acc.data.decode())
+        .expect("destination not returned by simulation");
+    unpack_token_account(&data)
+        .unwrap()
+        .amount
+        .saturating_sub(balance_before)
+}
+
+#[derive(Debug, Serialize)]
+pub struct SwapOutput {
+    pub route: Route,
+    pub mint_in: String,
+    pub mint_out: String,
+    pub in_amount: u64,
+    pub quoted_out_amount: u64,
+
+    /// `quoted_out_amount` less `--slippage-bps`
+    pub min_out_amount: u64,
+
+    /// None if the tx was only printed for offline signing
+    pub simulated_out_amount: Option<u64>,
+
+    /// Token account of mint_out that receives the output
+    pub destination: String,
+
+    pub signature: Option<String>,
+}
+
+impl CmdOutput for SwapOutput {
+    type Row = Self;
+
+    fn text(&self) -> String {
+        match (&self.signature, self.simulated_out_amount) {
+            (Some(signature), Some(out_amount)) => format!(
+                "Swapped {} atomics of {} for ~{} atomics of {} into {}: {}",
+                self.in_amount,
+                self.mint_in,
+                out_amount,
+                self.mint_out,
+                self.destination,
+                signature
+            ),
+            _ => String::new(),
+        }
+    }
+
+    fn csv_rows(&self) -> Vec<&Self> {
+        vec![self]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn swap_ix() -> Instruction {
+        Instruction::new_with_bytes(stakedex_interface::ID, &[1], vec![])
+    }
+
+    #[test]
+    fn test_swap_ixs() {
+        let user = Pubkey::new_unique();
+        let mint_out = Pubkey::new_unique();
+        let wsol_account = get_associated_token_address_with_program_id(
+            &user,
+            &spl_token::native_mint::ID,
+            &spl_token::ID,
+        );
+        let programs =
+            |ixs: &[Instruction]| -> Vec<Pubkey> { ixs.iter().map(|ix| ix.program_id).collect() };
+
+        let ixs = swap_ixs(&user, &mint_out, &spl_token::ID, None, swap_ix());
+        assert_eq!(
+            programs(&ixs),
+            [spl_associated_token_account::ID, stakedex_interface::ID]
+        );
+
+        let mut sol_in = SolIn {
+            wsol_account,
+            lamports: 5,
+            created: false,
+        };
+        let wrapped = [
+            spl_associated_token_account::ID,
+            spl_associated_token_account::ID,
+            system_program::ID,
+            spl_token::ID,
+            stakedex_interface::ID,
+        ];
+        // existing wSOL is left wrapped
+        let ixs = swap_ixs(&user, &mint_out, &spl_token::ID, Some(&sol_in), swap_ix());
+        assert_eq!(programs(&ixs), wrapped);
+        assert_eq!(
+            ixs[2],
+            system_instruction::transfer(&user, &wsol_account, 5)
+        );
+        assert_eq!(ixs[4], swap_ix());
+
+        sol_in.created = true;
+        let ixs = swap_ixs(&user, &mint_out, &spl_token::ID, Some(&sol_in), swap_ix());
+        assert_eq!(programs(&ixs[..5]), wrapped);
+        assert_eq!(
+            ixs[5],
+            spl_token::instruction::close_account(&spl_token::ID, &wsol_account, &user, &user, &[])
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_min_out_amount() {
+        assert_eq!(min_out_amount(1_000_000, 50), 995_000);
+        assert_eq!(min_out_amount(999, 1), 998);
+        assert_eq!(min_out_amount(1_000, 0), 1_000);
+        assert_eq!(min_out_amount(1_000, MAX_SLIPPAGE_BPS), 0);
+        assert_eq!(min_out_amount(u64::MAX, 0), u64::MAX);
+    }
+}
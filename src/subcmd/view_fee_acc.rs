@@ -1,8 +1,16 @@
+use std::slice;
+
 use clap::Args;
-use solana_sdk::{native_token::lamports_to_sol, program_pack::Pack, pubkey::Pubkey};
+use solana_sdk::pubkey::Pubkey;
 use stakedex_sdk_common::find_fee_token_acc;
 
-use super::SubcmdExec;
+use crate::{
+    output::{print_output, CmdOutput},
+    pool_utils::fetch_exchange_rates,
+    token_utils::{mint_decimals_of, unpack_token_account},
+};
+
+use super::{FeeAcc, SubcmdExec};
 
 #[derive(Args, Debug)]
 #[command(long_about = "View the fee token account for a xSOL mint")]
@@ -15,9 +23,88 @@ impl SubcmdExec for ViewFeeAccArgs {
     fn process_cmd(&self, args: &crate::Args) {
         let client = args.config.rpc_client();
         let fee_token_account = find_fee_token_acc(&self.mint).0;
-        let fetched = client.get_account(&fee_token_account).unwrap();
-        let parsed = spl_token::state::Account::unpack(&fetched.data).unwrap();
-        println!("Account: {fee_token_account}");
-        println!("Balance: {}", lamports_to_sol(parsed.amount));
+        let fetched = client
+            .get_multiple_accounts(&[fee_token_account, self.mint])
+            .unwrap();
+        let parsed = unpack_token_account(&fetched[0].as_ref().unwrap().data).unwrap();
+        let decimals = mint_decimals_of(fetched[1].as_ref()).unwrap();
+        let registry = args.mint_registry();
+        let info = registry.get(&self.mint);
+        let rate = info.and_then(|info| {
+            match fetch_exchange_rates(&client, slice::from_ref(info))
+                .unwrap()
+                .pop()
+                .unwrap()
+            {
+                Ok(r) => Some(r),
+                Err(e) => {
+                    args.output.log(format!(
+                        "[WARN] Could not value {} in SOL: {}",
+                        info.symbol, e
+                    ));
+                    None
+                }
+            }
+        });
+        let output = FeeAcc::new(
+            &self.mint,
+            info.map(|info| info.symbol.clone()),
+            &fee_token_account,
+            parsed.amount,
+            decimals,
+            rate.as_ref(),
+        );
+        print_output(args.output, &output);
+    }
+}
+
+impl CmdOutput for FeeAcc {
+    type Row = Self;
+
+    fn text(&self) -> String {
+        let mut lines = Vec::new();
+        if let Some(symbol) = &self.symbol {
+            lines.push(format!("Token: {symbol}"));
+        }
+        lines.push(format!("Account: {}", self.address));
+        lines.push(format!("Balance: {}", self.balance));
+        if let Some(sol_value) = self.sol_value {
+            lines.push(format!("SOL value: {sol_value}"));
+        }
+        lines.join("\n")
+    }
+
+    fn csv_rows(&self) -> Vec<&Self> {
+        vec![self]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pool_utils::ExchangeRate;
+
+    use super::*;
+
+    #[test]
+    fn test_fee_acc_balance() {
+        let mint = Pubkey::new_unique();
+        let acc = FeeAcc::new(&mint, None, &Pubkey::new_unique(), 1_000_001, 6, None);
+        assert_eq!(acc.balance, 1.000001);
+        assert_eq!(acc.sol_value, None);
+
+        let rate = ExchangeRate {
+            lamports: 11,
+            pool_tokens: 10,
+        };
+        let acc = FeeAcc::new(
+            &mint,
+            None,
+            &Pubkey::new_unique(),
+            2_000_000_000,
+            9,
+            Some(&rate),
+        );
+        assert_eq!(acc.balance, 2.0);
+        assert_eq!(acc.sol_value, Some(2.2));
     }
 }
@@ -1,68 +1,321 @@
-use base64::{engine::general_purpose, Engine};
-use borsh::ser::BorshSerialize;
 use clap::Args;
+use itertools::izip;
+use serde::Serialize;
 use solana_sdk::pubkey::Pubkey;
-use spl_governance::state::proposal_transaction::{AccountMetaData, InstructionData};
-use stakedex_interface::{WithdrawFeesIxArgs, WithdrawFeesKeys};
+use spl_associated_token_account::{
+    get_associated_token_address_with_program_id, instruction::create_associated_token_account,
+};
+use spl_token::{amount_to_ui_amount, ui_amount_to_amount};
 use stakedex_sdk_common::find_fee_token_acc;
 
-use super::SubcmdExec;
+use crate::{
+    gov_utils::{
+        encode_instruction_data, pack_proposal_transactions, propose, to_instruction_data,
+        ProposalArgs,
+    },
+    output::{print_output, CmdOutput},
+    token_utils::{
+        get_multiple_accounts_chunked, mint_decimals_of, token_program_of, unpack_token_account,
+    },
+    tx_utils::{build_tx, pack_instructions, send_or_sim_tx, TxSigners},
+};
+
+use super::{resolve_admin, withdraw_fees_instruction, SubcmdExec};
 
 #[derive(Args, Debug)]
 #[command(
-    long_about = "Outputs a base64-encoded spl-governance program instruction that withdraws fees to the specified token account when executed by the admin authority spl-governance PDA."
+    long_about = "Outputs base64-encoded spl-governance program instructions that withdraw fees to the specified token account when executed by the admin authority spl-governance PDA. With --propose, also creates a proposal executing them."
 )]
 pub struct WithdrawFeesArgs {
-    #[arg(help = "Mint of the token to withdraw fees for.")]
-    pub mint: Pubkey,
     #[arg(
-        help = "Destination token account to withdraw fees to. Defaults to the admin authority's associated token account if not provided."
+        help = "Mint of the token to withdraw fees for.",
+        required_unless_present = "all"
+    )]
+    pub mint: Option<Pubkey>,
+    #[arg(
+        help = "Destination token account to withdraw fees to. Defaults to the admin authority's associated token account if not provided.",
+        conflicts_with = "all"
     )]
     pub destination: Option<Pubkey>,
+
+    #[arg(
+        long,
+        help = "Withdraw fees of all xSOL mints in the mint registry to the admin authority's associated token accounts, creating them if they dont exist.",
+        default_value_t = false,
+        conflicts_with = "mint"
+    )]
+    pub all: bool,
+
+    #[arg(
+        long,
+        help = "Skip fee accounts with balances below this amount. Only used with --all.",
+        default_value_t = 0.0,
+        requires = "all"
+    )]
+    pub min_balance: f64,
+
+    #[command(flatten)]
+    pub proposal: ProposalArgs,
 }
-/// TODO: import from stakedex crate when onchain program is made public
-const ADMIN_AUTHORITY: &str = "A7jn1BA6LPHX8Wcmc8t476gjoLCG4PZakww19ZXfFRjX";
 
 impl SubcmdExec for WithdrawFeesArgs {
-    fn process_cmd(&self, _args: &crate::Args) {
-        let mint_token_account = find_fee_token_acc(&self.mint).0;
-        let admin = ADMIN_AUTHORITY.parse().unwrap();
-        let destination_token_account = self.destination.unwrap_or_else(|| {
-            spl_associated_token_account::get_associated_token_address(&admin, &self.mint)
-        });
-
-        let ix = stakedex_interface::withdraw_fees_ix(
-            WithdrawFeesKeys {
-                admin,
-                mint: self.mint,
-                fee_token_account: mint_token_account,
-                withdraw_to: destination_token_account,
-                token_program: spl_token::ID,
-            },
-            WithdrawFeesIxArgs {},
-        )
-        .unwrap();
-
-        let ix_data = InstructionData {
-            program_id: ix.program_id,
-            accounts: ix
-                .accounts
-                .iter()
-                .map(|acc| AccountMetaData {
-                    pubkey: acc.pubkey,
-                    is_signer: acc.is_signer,
-                    is_writable: acc.is_writable,
-                })
-                .collect(),
-            data: ix.data,
+    fn process_cmd(&self, args: &crate::Args) {
+        let admin = resolve_admin(args);
+        let withdrawals = match self.all {
+            true => self.withdrawals_for_all(args, &admin),
+            false => {
+                let mint = self.mint.unwrap();
+                let token_program = token_program_of(&args.config.rpc_client(), &mint).unwrap();
+                let destination = self.destination.unwrap_or_else(|| {
+                    get_associated_token_address_with_program_id(&admin, &mint, &token_program)
+                });
+                vec![Withdrawal {
+                    mint,
+                    destination,
+                    token_program,
+                }]
+            }
         };
+        let mut output = WithdrawFeesOutput {
+            withdrawals: Vec::new(),
+            created_token_accounts: Vec::new(),
+            signatures: Vec::new(),
+            proposal: None,
+        };
+        if withdrawals.is_empty() {
+            print_output(args.output, &output);
+            return;
+        }
+        if self.all {
+            create_missing_destinations(args, &admin, &withdrawals, &mut output);
+        }
+
+        let ix_datas: Vec<_> = withdrawals
+            .iter()
+            .map(|w| {
+                let ix_data = to_instruction_data(withdraw_fees_instruction(
+                    &admin,
+                    &w.mint,
+                    &w.destination,
+                    &w.token_program,
+                ));
+                output.withdrawals.push(WithdrawFeesInstruction {
+                    mint: w.mint.to_string(),
+                    destination: w.destination.to_string(),
+                    token_program: w.token_program.to_string(),
+                    instruction: encode_instruction_data(&ix_data),
+                });
+                ix_data
+            })
+            .collect();
+
+        if self.proposal.propose {
+            let default_name = match withdrawals.as_slice() {
+                [w] => format!("Withdraw stakedex fees for {}", w.mint),
+                _ => format!("Withdraw stakedex fees for {} mints", withdrawals.len()),
+            };
+            let proposal = propose(
+                args,
+                &self.proposal,
+                &admin,
+                &default_name,
+                pack_proposal_transactions(ix_datas),
+            );
+            output.proposal = Some(proposal.to_string());
+        }
+        print_output(args.output, &output);
+    }
+}
+
+impl WithdrawFeesArgs {
+    /// Withdrawals of every mint in the mint registry with a fee balance of at least `--min-balance`
+    fn withdrawals_for_all(&self, args: &crate::Args, admin: &Pubkey) -> Vec<Withdrawal> {
+        let client = args.config.rpc_client();
+        let registry = args.mint_registry();
+        let fee_accs: Vec<_> = registry
+            .mints
+            .iter()
+            .map(|info| find_fee_token_acc(&info.mint).0)
+            .collect();
+        // fee accs and mints in one batch
+        let mut fetched =
+            get_multiple_accounts_chunked(&client, &[fee_accs.clone(), registry.mints()].concat())
+                .unwrap();
+        let fetched_mints = fetched.split_off(fee_accs.len());
+
+        let mut withdrawals = Vec::new();
+        for (info, addr, opt, mint_opt) in izip!(&registry.mints, fee_accs, fetched, fetched_mints)
+        {
+            let mint = info.mint;
+            let acc = match opt {
+                Some(a) => a,
+                None => {
+                    args.output.log(format!(
+                        "[WARN] Missing fee acc {} for token {}",
+                        addr, mint
+                    ));
+                    continue;
+                }
+            };
+            let parsed = unpack_token_account(&acc.data).unwrap();
+            let decimals = mint_decimals_of(mint_opt.as_ref()).unwrap_or(info.decimals);
+            let min_balance = ui_amount_to_amount(self.min_balance, decimals);
+            if parsed.amount == 0 || parsed.amount < min_balance {
+                args.output.log(format!(
+                    "Skipping {} with balance {}",
+                    info.symbol,
+                    amount_to_ui_amount(parsed.amount, decimals)
+                ));
+                continue;
+            }
+            // the fee token account is owned by the mint's token program
+            let token_program = acc.owner;
+            withdrawals.push(Withdrawal {
+                mint,
+                destination: get_associated_token_address_with_program_id(
+                    admin,
+                    &mint,
+                    &token_program,
+                ),
+                token_program,
+            });
+        }
+        withdrawals
+    }
+}
+
+/// Creates the admin authority's associated token accounts of `withdrawals` that dont exist yet
+fn create_missing_destinations(
+    args: &crate::Args,
+    admin: &Pubkey,
+    withdrawals: &[Withdrawal],
+    output: &mut WithdrawFeesOutput,
+) {
+    let client = args.config.rpc_client();
+    let destinations: Vec<_> = withdrawals.iter().map(|w| w.destination).collect();
+    let fetched = get_multiple_accounts_chunked(&client, &destinations).unwrap();
+    let missing: Vec<_> = withdrawals
+        .iter()
+        .zip(fetched)
+        .filter(|(_, opt)| opt.is_none())
+        .map(|(w, _)| w)
+        .collect();
+    if missing.is_empty() {
+        return;
+    }
+    let payer = args.config.signer(&args.offline);
+    let payer_pk = payer.pubkey();
+    let create_ixs: Vec<_> = missing
+        .iter()
+        .map(|w| {
+            args.output.log(format!(
+                "Creating admin token acc {} of mint {}",
+                w.destination, w.mint
+            ));
+            output
+                .created_token_accounts
+                .push(w.destination.to_string());
+            create_associated_token_account(&payer_pk, admin, &w.mint, &w.token_program)
+        })
+        .collect();
+    let signers = TxSigners::new(args, payer);
+    for ixs in pack_instructions(create_ixs, &payer_pk) {
+        let tx = build_tx(args, &client, &ixs, &signers);
+        if let Some(signature) = send_or_sim_tx(args, &client, &tx, &signers) {
+            output.signatures.push(signature.to_string());
+        }
+    }
+}
+
+struct Withdrawal {
+    mint: Pubkey,
+    destination: Pubkey,
+    token_program: Pubkey,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WithdrawFeesInstruction {
+    pub mint: String,
+
+    pub destination: String,
 
-        let serialized_data: Vec<u8> = ix_data.try_to_vec().unwrap();
-        let ix_base64 = general_purpose::STANDARD.encode(serialized_data);
+    pub token_program: String,
 
-        println!(
-            "Instruction for withdrawing fees to {}:\n{}",
-            destination_token_account, ix_base64
-        );
+    /// base64-encoded spl-governance InstructionData
+    pub instruction: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WithdrawFeesOutput {
+    pub withdrawals: Vec<WithdrawFeesInstruction>,
+
+    /// Admin authority associated token accounts created for --all
+    pub created_token_accounts: Vec<String>,
+
+    /// Signatures of the transactions creating `created_token_accounts`
+    pub signatures: Vec<String>,
+
+    /// Some if --propose
+    pub proposal: Option<String>,
+}
+
+impl CmdOutput for WithdrawFeesOutput {
+    type Row = WithdrawFeesInstruction;
+
+    fn text(&self) -> String {
+        if self.withdrawals.is_empty() {
+            return "No fees to withdraw".to_owned();
+        }
+        let mut lines: Vec<_> = self
+            .withdrawals
+            .iter()
+            .map(|w| {
+                format!(
+                    "Instruction for withdrawing {} fees to {}:\n{}",
+                    w.mint, w.destination, w.instruction
+                )
+            })
+            .collect();
+        if let Some(proposal) = &self.proposal {
+            lines.push(format!("Proposal: {}", proposal));
+        }
+        lines.join("\n")
+    }
+
+    fn csv_rows(&self) -> Vec<&WithdrawFeesInstruction> {
+        self.withdrawals.iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_withdraw_fees_instruction_data() {
+        let admin = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let destination =
+            get_associated_token_address_with_program_id(&admin, &mint, &spl_token::ID);
+        let ix_data = to_instruction_data(withdraw_fees_instruction(
+            &admin,
+            &mint,
+            &destination,
+            &spl_token::ID,
+        ));
+        assert_eq!(ix_data.program_id, stakedex_interface::ID);
+        let meta_of = |pubkey: &Pubkey| {
+            ix_data
+                .accounts
+                .iter()
+                .find(|a| a.pubkey == *pubkey)
+                .unwrap()
+        };
+        // executed by the governance PDA, which signs for the admin authority
+        assert!(meta_of(&admin).is_signer);
+        assert!(meta_of(&find_fee_token_acc(&mint).0).is_writable);
+        assert!(meta_of(&destination).is_writable);
+        assert!(!meta_of(&mint).is_signer);
+        assert_eq!(ix_data.accounts.len(), 5);
     }
 }
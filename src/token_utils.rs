@@ -0,0 +1,199 @@
+use std::error::Error;
+
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{account::Account, program_error::ProgramError, pubkey::Pubkey};
+use spl_token_2022::{
+    extension::StateWithExtensions,
+    state::{Account as TokenAccount, Mint},
+};
+
+/// Max number of accounts a single getMultipleAccounts call accepts
+pub const MAX_MULTIPLE_ACCOUNTS: usize = 100;
+
+/// getMultipleAccounts in batches of MAX_MULTIPLE_ACCOUNTS
+pub fn get_multiple_accounts_chunked(
+    client: &RpcClient,
+    pubkeys: &[Pubkey],
+) -> Result<Vec<Option<Account>>, Box<dyn Error>> {
+    let mut res = Vec::with_capacity(pubkeys.len());
+    for chunk in pubkeys.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        res.extend(client.get_multiple_accounts(chunk)?);
+    }
+    Ok(res)
+}
+
+/// Decimals of each of `mints`. None if the mint doesnt exist or is not a valid mint
+pub fn fetch_mint_decimals(
+    client: &RpcClient,
+    mints: &[Pubkey],
+) -> Result<Vec<Option<u8>>, Box<dyn Error>> {
+    Ok(get_multiple_accounts_chunked(client, mints)?
+        .iter()
+        .map(|opt| mint_decimals_of(opt.as_ref()))
+        .collect())
+}
+
+/// Decimals of a fetched mint account. None if the account doesnt exist or is not a valid mint
+pub fn mint_decimals_of(account: Option<&Account>) -> Option<u8> {
+    let account = account?;
+    if !is_token_program(&account.owner) {
+        return None;
+    }
+    unpack_mint(&account.data).ok().map(|m| m.decimals)
+}
+
+/// spl-token or token-2022
+pub fn is_token_program(program_id: &Pubkey) -> bool {
+    *program_id == spl_token::ID || *program_id == spl_token_2022::ID
+}
+
+/// The token program that owns `mint`
+pub fn token_program_of(client: &RpcClient, mint: &Pubkey) -> Result<Pubkey, Box<dyn Error>> {
+    let account = client.get_account(mint)?;
+    token_program_of_account(mint, &account)
+}
+
+/// The token programs that own each of `mints`
+pub fn token_programs_of(
+    client: &RpcClient,
+    mints: &[Pubkey],
+) -> Result<Vec<Pubkey>, Box<dyn Error>> {
+    let fetched = get_multiple_accounts_chunked(client, mints)?;
+    mints
+        .iter()
+        .zip(fetched)
+        .map(|(mint, opt)| {
+            let account = opt.ok_or_else(|| format!("mint {} does not exist", mint))?;
+            token_program_of_account(mint, &account)
+        })
+        .collect()
+}
+
+fn token_program_of_account(mint: &Pubkey, account: &Account) -> Result<Pubkey, Box<dyn Error>> {
+    match is_token_program(&account.owner) {
+        true => Ok(account.owner),
+        false => Err(format!("{} is not a mint, owned by {}", mint, account.owner).into()),
+    }
+}
+
+/// Unpacks a spl-token or token-2022 token account, ignoring any extensions
+pub fn unpack_token_account(data: &[u8]) -> Result<TokenAccount, ProgramError> {
+    Ok(StateWithExtensions::<TokenAccount>::unpack(data)?.base)
+}
+
+/// Unpacks a spl-token or token-2022 mint, ignoring any extensions
+pub fn unpack_mint(data: &[u8]) -> Result<Mint, ProgramError> {
+    Ok(StateWithExtensions::<Mint>::unpack(data)?.base)
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::program_pack::Pack;
+    use spl_token_2022::{
+        extension::{
+            immutable_owner::ImmutableOwner,
+            mint_close_authority::MintCloseAuthority,
+            transfer_fee::{TransferFeeAmount, TransferFeeConfig},
+            ExtensionType, StateWithExtensionsMut,
+        },
+        state::AccountState,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_unpack_legacy_token_account() {
+        let mint = Pubkey::new_unique();
+        let account = spl_token::state::Account {
+            mint,
+            owner: Pubkey::new_unique(),
+            amount: 1_000,
+            state: spl_token::state::AccountState::Initialized,
+            ..Default::default()
+        };
+        let mut data = vec![0; spl_token::state::Account::LEN];
+        spl_token::state::Account::pack(account, &mut data).unwrap();
+        let unpacked = unpack_token_account(&data).unwrap();
+        assert_eq!(unpacked.mint, mint);
+        assert_eq!(unpacked.amount, 1_000);
+    }
+
+    #[test]
+    fn test_unpack_legacy_mint() {
+        let mint = spl_token::state::Mint {
+            decimals: 6,
+            is_initialized: true,
+            ..Default::default()
+        };
+        let mut data = vec![0; spl_token::state::Mint::LEN];
+        spl_token::state::Mint::pack(mint, &mut data).unwrap();
+        assert_eq!(unpack_mint(&data).unwrap().decimals, 6);
+    }
+
+    #[test]
+    fn test_unpack_token_2022_account_with_extensions() {
+        let mint = Pubkey::new_unique();
+        let len = ExtensionType::get_account_len::<TokenAccount>(&[
+            ExtensionType::ImmutableOwner,
+            ExtensionType::TransferFeeAmount,
+        ]);
+        assert!(len > spl_token::state::Account::LEN);
+        let mut data = vec![0; len];
+        let mut state =
+            StateWithExtensionsMut::<TokenAccount>::unpack_uninitialized(&mut data).unwrap();
+        state.base = TokenAccount {
+            mint,
+            owner: Pubkey::new_unique(),
+            amount: 1_000,
+            state: AccountState::Initialized,
+            ..Default::default()
+        };
+        state.pack_base();
+        state.init_account_type().unwrap();
+        state.init_extension::<ImmutableOwner>(true).unwrap();
+        state.init_extension::<TransferFeeAmount>(true).unwrap();
+
+        let unpacked = unpack_token_account(&data).unwrap();
+        assert_eq!(unpacked.mint, mint);
+        assert_eq!(unpacked.amount, 1_000);
+        // a mint is not a token account
+        assert!(unpack_mint(&data).is_err());
+    }
+
+    #[test]
+    fn test_unpack_token_2022_mint_with_extensions() {
+        let len = ExtensionType::get_account_len::<Mint>(&[
+            ExtensionType::MintCloseAuthority,
+            ExtensionType::TransferFeeConfig,
+        ]);
+        assert!(len > spl_token::state::Mint::LEN);
+        let mut data = vec![0; len];
+        let mut state = StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut data).unwrap();
+        state.base = Mint {
+            decimals: 9,
+            is_initialized: true,
+            ..Default::default()
+        };
+        state.pack_base();
+        state.init_account_type().unwrap();
+        state.init_extension::<MintCloseAuthority>(true).unwrap();
+        state.init_extension::<TransferFeeConfig>(true).unwrap();
+
+        assert_eq!(unpack_mint(&data).unwrap().decimals, 9);
+        assert!(unpack_token_account(&data).is_err());
+    }
+
+    #[test]
+    fn test_token_program_of_account() {
+        let mint = Pubkey::new_unique();
+        for owner in [spl_token::ID, spl_token_2022::ID] {
+            let account = Account {
+                owner,
+                ..Default::default()
+            };
+            assert_eq!(token_program_of_account(&mint, &account).unwrap(), owner);
+        }
+        let account = Account::default();
+        assert!(token_program_of_account(&mint, &account).is_err());
+    }
+}
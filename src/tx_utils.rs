@@ -1,14 +1,652 @@
-use solana_client::rpc_client::RpcClient;
-use solana_sdk::transaction::Transaction;
+use std::{process, thread::sleep, time::Duration};
 
-pub fn send_or_sim_tx(args: &crate::Args, rpc_client: &RpcClient, tx: &Transaction) {
+use base64::{engine::general_purpose, Engine};
+
+use solana_client::{
+    client_error::ClientError,
+    nonce_utils,
+    rpc_client::RpcClient,
+    rpc_config::{RpcSendTransactionConfig, RpcSimulateTransactionConfig},
+    rpc_response::RpcPrioritizationFee,
+};
+use solana_sdk::{
+    compute_budget::ComputeBudgetInstruction,
+    hash::Hash,
+    instruction::Instruction,
+    message::Message,
+    packet::PACKET_DATA_SIZE,
+    pubkey::Pubkey,
+    signature::Signature,
+    signer::{signers::Signers, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use solana_transaction_status::UiTransactionEncoding;
+
+use crate::{argparse::TxEncoding, output::OutputFormat};
+
+/// Max compute units a transaction can request
+pub const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+
+/// Extra compute units to request on top of the simulated units consumed,
+/// in percent, to account for state changing between simulation and execution
+pub const COMPUTE_UNIT_LIMIT_HEADROOM_PCT: u64 = 20;
+
+/// Signers of a transaction built with [`build_tx`]
+pub struct TxSigners {
+    /// Fee payer first, followed by the nonce authority if distinct
+    pub signers: Vec<Box<dyn Signer>>,
+
+    /// Some if `--nonce` was provided
+    pub nonce_authority: Option<Pubkey>,
+}
+
+impl TxSigners {
+    pub fn new(args: &crate::Args, payer: Box<dyn Signer>) -> Self {
+        let payer_pk = payer.pubkey();
+        let mut signers = vec![payer];
+        let nonce_authority = match (&args.offline.nonce, &args.offline.nonce_authority) {
+            (None, _) => None,
+            (Some(_), None) => Some(payer_pk),
+            (Some(_), Some(path)) => {
+                let authority = args.offline.signer_from_path(path);
+                let authority_pk = authority.pubkey();
+                if authority_pk != payer_pk {
+                    signers.push(authority);
+                }
+                Some(authority_pk)
+            }
+        };
+        Self {
+            signers,
+            nonce_authority,
+        }
+    }
+
+    pub fn payer(&self) -> Pubkey {
+        self.signers[0].pubkey()
+    }
+
+    /// Adds a signer required by the transaction's instructions, unless it already signs
+    pub fn add_signer(&mut self, signer: Box<dyn Signer>) {
+        let pubkey = signer.pubkey();
+        if self.signers.iter().all(|s| s.pubkey() != pubkey) {
+            self.signers.push(signer);
+        }
+    }
+}
+
+/// Builds and signs a transaction for `ixs` with compute budget instructions prepended:
+/// - CU limit = simulated units consumed + COMPUTE_UNIT_LIMIT_HEADROOM_PCT
+/// - CU price = `--priority-fee-percentile` of recent prioritization fees of the
+///   transaction's writable accounts, capped at `--priority-fee-cap`
+///
+/// If the blockhash is fixed by `--blockhash`, the compute budget is not computed dynamically
+/// so that the same message is built when signing offline and when submitting.
+///
+/// If `--nonce` is provided, the nonce advance instruction is prepended
+/// and the durable nonce is used as the blockhash.
+pub fn build_tx(
+    args: &crate::Args,
+    rpc_client: &RpcClient,
+    ixs: &[Instruction],
+    signers: &TxSigners,
+) -> Transaction {
+    let payer = signers.payer();
+    let blockhash = match (args.offline.blockhash, args.offline.nonce) {
+        (Some(blockhash), _) => blockhash,
+        (None, Some(nonce)) => {
+            let account = nonce_utils::get_account_with_commitment(
+                rpc_client,
+                &nonce,
+                rpc_client.commitment(),
+            )
+            .unwrap();
+            nonce_utils::data_from_account(&account)
+                .unwrap()
+                .blockhash()
+        }
+        (None, None) => rpc_client.get_latest_blockhash().unwrap(),
+    };
+
+    let (cu_limit, cu_price) = match args.offline.blockhash {
+        Some(_) => (
+            args.compute_unit_limit.unwrap_or(MAX_COMPUTE_UNIT_LIMIT),
+            args.compute_unit_price.unwrap_or(0),
+        ),
+        None => (
+            args.compute_unit_limit
+                .unwrap_or_else(|| simulate_compute_unit_limit(args, rpc_client, ixs, &payer)),
+            args.compute_unit_price
+                .unwrap_or_else(|| recent_priority_fee(args, rpc_client, ixs, &payer)),
+        ),
+    };
+
+    let ixs = with_compute_budget(cu_limit, cu_price, ixs);
+    let nonce = args.offline.nonce.zip(signers.nonce_authority);
+    let msg = tx_message(ixs, &payer, &blockhash, nonce.as_ref());
+    args.output.log(format!(
+        "Compute unit limit: {cu_limit}, price: {cu_price} micro-lamports"
+    ));
+    match args.offline.output_tx {
+        // dont prompt any signers since the tx will be signed externally
+        Some(_) => Transaction::new_unsigned(msg),
+        None => Transaction::new(&signers.signers, msg, blockhash),
+    }
+}
+
+/// Message of `ixs` with the given blockhash.
+/// If `nonce` is (nonce account, nonce authority), the nonce advance instruction is prepended
+/// and `blockhash` must be the durable nonce.
+fn tx_message(
+    ixs: Vec<Instruction>,
+    payer: &Pubkey,
+    blockhash: &Hash,
+    nonce: Option<&(Pubkey, Pubkey)>,
+) -> Message {
+    match nonce {
+        Some((nonce, nonce_authority)) => {
+            let mut msg = Message::new_with_nonce(ixs, Some(payer), nonce, nonce_authority);
+            msg.recent_blockhash = *blockhash;
+            msg
+        }
+        None => Message::new_with_blockhash(&ixs, Some(payer), blockhash),
+    }
+}
+
+fn simulate_compute_unit_limit(
+    args: &crate::Args,
+    rpc_client: &RpcClient,
+    ixs: &[Instruction],
+    payer: &Pubkey,
+) -> u32 {
+    let sim_msg = Message::new(
+        &with_compute_budget(MAX_COMPUTE_UNIT_LIMIT, 0, ixs),
+        Some(payer),
+    );
+    let sim_result = rpc_client
+        .simulate_transaction_with_config(
+            &Transaction::new_unsigned(sim_msg),
+            RpcSimulateTransactionConfig {
+                sig_verify: false,
+                replace_recent_blockhash: true,
+                ..Default::default()
+            },
+        )
+        .unwrap()
+        .value;
+    match sim_result.units_consumed {
+        Some(units) if sim_result.err.is_none() => compute_unit_limit_with_headroom(units),
+        _ => {
+            args.output.log(format!(
+                "[WARN] Simulation failed, using max compute unit limit. Err: {:?}",
+                sim_result.err
+            ));
+            MAX_COMPUTE_UNIT_LIMIT
+        }
+    }
+}
+
+fn recent_priority_fee(
+    args: &crate::Args,
+    rpc_client: &RpcClient,
+    ixs: &[Instruction],
+    payer: &Pubkey,
+) -> u64 {
+    let msg = Message::new(ixs, Some(payer));
+    let writable_accounts: Vec<Pubkey> = msg
+        .account_keys
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| msg.is_writable(*i))
+        .map(|(_, pk)| *pk)
+        .collect();
+    let recent_fees = rpc_client
+        .get_recent_prioritization_fees(&writable_accounts)
+        .unwrap();
+    priority_fee(
+        &recent_fees,
+        args.priority_fee_percentile,
+        args.priority_fee_cap,
+    )
+}
+
+pub fn with_compute_budget(cu_limit: u32, cu_price: u64, ixs: &[Instruction]) -> Vec<Instruction> {
+    let mut res = vec![
+        ComputeBudgetInstruction::set_compute_unit_limit(cu_limit),
+        ComputeBudgetInstruction::set_compute_unit_price(cu_price),
+    ];
+    res.extend_from_slice(ixs);
+    res
+}
+
+/// Splits `ixs` into as few transactions as possible, in order,
+/// such that each still fits in a packet once built with [`build_tx`]
+pub fn pack_instructions(ixs: Vec<Instruction>, payer: &Pubkey) -> Vec<Vec<Instruction>> {
+    let mut res = Vec::new();
+    let mut current = Vec::new();
+    for ix in ixs {
+        current.push(ix);
+        if current.len() > 1 && max_tx_len(&current, payer) > PACKET_DATA_SIZE {
+            let ix = current.pop().unwrap();
+            res.push(std::mem::replace(&mut current, vec![ix]));
+        }
+    }
+    if !current.is_empty() {
+        res.push(current);
+    }
+    res
+}
+
+/// Upper bound of the serialized size of the transaction [`build_tx`] builds for `ixs`:
+/// with compute budget and nonce advance instructions and a separate nonce authority signer
+pub fn max_tx_len(ixs: &[Instruction], payer: &Pubkey) -> usize {
+    let ixs = with_compute_budget(MAX_COMPUTE_UNIT_LIMIT, u64::MAX, ixs);
+    let msg = Message::new_with_nonce(
+        ixs,
+        Some(payer),
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+    );
+    // compact-u16 signature count + signatures + message
+    let num_signatures = usize::from(msg.header.num_required_signatures);
+    1 + num_signatures * 64 + msg.serialize().len()
+}
+
+fn compute_unit_limit_with_headroom(units_consumed: u64) -> u32 {
+    let with_headroom = units_consumed * (100 + COMPUTE_UNIT_LIMIT_HEADROOM_PCT) / 100;
+    with_headroom
+        .try_into()
+        .unwrap_or(MAX_COMPUTE_UNIT_LIMIT)
+        .min(MAX_COMPUTE_UNIT_LIMIT)
+}
+
+/// Returns the `percentile`-th (nearest-rank) prioritization fee in micro-lamports per CU,
+/// capped at `cap`. 0 if no recent fees.
+fn priority_fee(recent_fees: &[RpcPrioritizationFee], percentile: u8, cap: u64) -> u64 {
+    let mut fees: Vec<u64> = recent_fees.iter().map(|f| f.prioritization_fee).collect();
+    if fees.is_empty() {
+        return 0;
+    }
+    fees.sort_unstable();
+    let rank = (usize::from(percentile) * fees.len()).div_ceil(100);
+    let fee = fees[rank.saturating_sub(1)];
+    fee.min(cap)
+}
+
+/// Max number of times to re-sign a transaction with a fresh blockhash
+/// after its blockhash expired without it landing
+pub const MAX_RESIGNS: usize = 2;
+
+/// Interval between signature status polls and rebroadcasts
+pub const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Final status of a sent transaction
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TxStatus {
+    /// Landed successfully at the RPC client's commitment level
+    Confirmed(Signature),
+
+    /// Blockhash expired without the transaction landing.
+    /// Nothing was executed, so it is safe to retry.
+    Expired(Signature),
+
+    /// Landed with an error or was rejected by preflight simulation
+    Failed(Signature, TransactionError),
+}
+
+/// Returns the signature of the transaction if it was sent and confirmed,
+/// None if it was only simulated or signed offline.
+/// The `signature(s)` of subcommand outputs are these, so they are None or empty in those cases too.
+pub fn send_or_sim_tx(
+    args: &crate::Args,
+    rpc_client: &RpcClient,
+    tx: &Transaction,
+    signers: &TxSigners,
+) -> Option<Signature> {
+    if let Some(encoding) = args.offline.output_tx {
+        print_unsigned_message(args.output, tx, encoding);
+        return None;
+    }
+    if args.offline.sign_only {
+        print_sign_only(args.output, tx);
+        return None;
+    }
     if args.dry_run {
         let result = rpc_client.simulate_transaction(tx).unwrap();
-        println!("Simulate result: {:?}", result);
-    } else {
-        let signature = rpc_client
-            .send_and_confirm_transaction_with_spinner(tx)
-            .unwrap();
-        println!("Signature: {}", signature);
+        args.output.log(format!("Simulate result: {:?}", result));
+        return None;
+    }
+    // cant re-sign if the blockhash was provided by the user
+    let max_resigns = match args.offline.has_fixed_blockhash() {
+        true => 0,
+        false => MAX_RESIGNS,
+    };
+    match send_and_confirm(
+        args.output,
+        rpc_client,
+        tx,
+        &signers.signers,
+        max_resigns,
+        POLL_INTERVAL,
+    )
+    .unwrap()
+    {
+        TxStatus::Confirmed(signature) => {
+            args.output.log(format!("Signature: {}", signature));
+            Some(signature)
+        }
+        TxStatus::Expired(signature) => {
+            match args.offline.nonce {
+                Some(nonce) => args.output.log(format!(
+                    "Transaction {signature} has not landed yet. It remains valid until nonce account {nonce} is advanced."
+                )),
+                None => args.output.log(format!(
+                    "Transaction {signature} expired without landing after {max_resigns} re-signs. Nothing was executed, it is safe to retry."
+                )),
+            }
+            process::exit(1);
+        }
+        TxStatus::Failed(signature, err) => {
+            args.output
+                .log(format!("Transaction {signature} failed: {err}"));
+            process::exit(1);
+        }
+    }
+}
+
+fn print_unsigned_message(output: OutputFormat, tx: &Transaction, encoding: TxEncoding) {
+    let data = tx.message.serialize();
+    let encoded = match encoding {
+        TxEncoding::Base58 => bs58::encode(data).into_string(),
+        TxEncoding::Base64 => general_purpose::STANDARD.encode(data),
+    };
+    output.log(format!(
+        "Unsigned transaction message ({encoding:?}):\n{encoded}"
+    ));
+    output.log("\nRequired signers:");
+    let num_signers = usize::from(tx.message.header.num_required_signatures);
+    for (i, pubkey) in tx.message.account_keys[..num_signers].iter().enumerate() {
+        let role = match i {
+            0 => " (fee payer)",
+            _ => "",
+        };
+        output.log(format!("  {pubkey}{role}"));
+    }
+    output.log(format!(
+        "\nThe message uses blockhash {}. Unless it is a durable nonce (--nonce), it expires in ~1 minute, so collect all signatures and submit promptly.",
+        tx.message.recent_blockhash
+    ));
+}
+
+fn print_sign_only(output: OutputFormat, tx: &Transaction) {
+    output.log(format!("Blockhash: {}", tx.message.recent_blockhash));
+    let num_signers = usize::from(tx.message.header.num_required_signatures);
+    let (present, absent): (Vec<_>, Vec<_>) = tx.message.account_keys[..num_signers]
+        .iter()
+        .zip(tx.signatures.iter())
+        .partition(|(_, signature)| **signature != Signature::default());
+    if !present.is_empty() {
+        output.log("Signers (Pubkey=Signature):");
+        for (pubkey, signature) in present {
+            output.log(format!("  {pubkey}={signature}"));
+        }
+    }
+    if !absent.is_empty() {
+        output.log("Absent Signers (Pubkey):");
+        for (pubkey, _) in absent {
+            output.log(format!("  {pubkey}"));
+        }
+    }
+}
+
+/// Sends `tx` and rebroadcasts it until it lands or its blockhash expires.
+///
+/// If the blockhash expired without the transaction landing, the transaction can no longer be executed,
+/// so it is re-signed with a fresh blockhash and sent again, up to `max_resigns` times.
+/// Rebroadcast and re-sign progress is logged with `output`.
+pub fn send_and_confirm<T: Signers>(
+    output: OutputFormat,
+    rpc_client: &RpcClient,
+    tx: &Transaction,
+    signers: &T,
+    max_resigns: usize,
+    poll_interval: Duration,
+) -> Result<TxStatus, ClientError> {
+    let commitment = rpc_client.commitment();
+    let mut tx = tx.clone();
+    // upper bound of tx's blockhash's last valid block height
+    // since tx's blockhash must be older than the latest blockhash
+    let (_, mut last_valid_block_height) =
+        rpc_client.get_latest_blockhash_with_commitment(commitment)?;
+    let mut resigns = 0;
+    loop {
+        let signature = tx.signatures[0];
+        if let Err(e) = rpc_client.send_transaction_with_config(
+            &tx,
+            RpcSendTransactionConfig {
+                preflight_commitment: Some(commitment.commitment),
+                encoding: Some(UiTransactionEncoding::Base64),
+                ..Default::default()
+            },
+        ) {
+            return match e.get_transaction_error() {
+                Some(err) => Ok(TxStatus::Failed(signature, err)),
+                None => Err(e),
+            };
+        }
+        loop {
+            sleep(poll_interval);
+            // check expiry before status so that a None status after expiry
+            // means the transaction can never land
+            let expired =
+                rpc_client.get_block_height_with_commitment(commitment)? > last_valid_block_height;
+            if let Some(status) = rpc_client
+                .get_signature_statuses(&[signature])?
+                .value
+                .pop()
+                .flatten()
+            {
+                if let Some(err) = status.err {
+                    return Ok(TxStatus::Failed(signature, err));
+                }
+                if status.satisfies_commitment(commitment) {
+                    return Ok(TxStatus::Confirmed(signature));
+                }
+                // landed but not yet at commitment level, wait without rebroadcasting
+                continue;
+            }
+            if expired {
+                break;
+            }
+            if let Err(e) = rpc_client.send_transaction_with_config(
+                &tx,
+                RpcSendTransactionConfig {
+                    skip_preflight: true,
+                    encoding: Some(UiTransactionEncoding::Base64),
+                    ..Default::default()
+                },
+            ) {
+                output.log(format!("[WARN] Rebroadcast of {signature} failed: {e}"));
+            }
+        }
+        if resigns >= max_resigns {
+            return Ok(TxStatus::Expired(signature));
+        }
+        resigns += 1;
+        let (blockhash, lvbh) = rpc_client.get_latest_blockhash_with_commitment(commitment)?;
+        tx.try_sign(signers, blockhash)?;
+        last_valid_block_height = lvbh;
+        output.log(format!(
+            "Blockhash of {signature} expired without landing, re-signed as {}",
+            tx.signatures[0]
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use solana_client::{mock_sender::Mocks, rpc_request::RpcRequest};
+    use solana_sdk::{
+        compute_budget,
+        instruction::InstructionError,
+        signature::Keypair,
+        signer::Signer,
+        system_instruction::{self, SystemInstruction},
+        system_program,
+    };
+
+    use super::*;
+
+    fn test_tx(payer: &Keypair) -> Transaction {
+        let ix = system_instruction::transfer(&payer.pubkey(), &Pubkey::new_unique(), 1);
+        Transaction::new(
+            &[payer],
+            Message::new(&[ix], Some(&payer.pubkey())),
+            Hash::new_unique(),
+        )
+    }
+
+    fn send_with_mock(url: &str, mocks: Mocks) -> (Signature, TxStatus) {
+        let rpc_client = RpcClient::new_mock_with_mocks(url.to_owned(), mocks);
+        let payer = Keypair::new();
+        let tx = test_tx(&payer);
+        let status = send_and_confirm(
+            OutputFormat::Text,
+            &rpc_client,
+            &tx,
+            &[&payer],
+            0,
+            Duration::ZERO,
+        )
+        .unwrap();
+        (tx.signatures[0], status)
+    }
+
+    #[test]
+    fn test_send_and_confirm_confirmed() {
+        let (signature, status) = send_with_mock("succeeds", Mocks::default());
+        assert_eq!(status, TxStatus::Confirmed(signature));
+    }
+
+    #[test]
+    fn test_send_and_confirm_failed() {
+        let (signature, status) = send_with_mock("instruction_error", Mocks::default());
+        assert_eq!(
+            status,
+            TxStatus::Failed(
+                signature,
+                TransactionError::InstructionError(0, InstructionError::UninitializedAccount)
+            )
+        );
+    }
+
+    #[test]
+    fn test_send_and_confirm_expired() {
+        // mock sender's latest blockhash is valid until block height 1234
+        let mocks = Mocks::from([(RpcRequest::GetBlockHeight, json!(1235))]);
+        let (signature, status) = send_with_mock("sig_not_found", mocks);
+        assert_eq!(status, TxStatus::Expired(signature));
+    }
+
+    fn fees(fees: &[u64]) -> Vec<RpcPrioritizationFee> {
+        fees.iter()
+            .enumerate()
+            .map(|(slot, prioritization_fee)| RpcPrioritizationFee {
+                slot: slot.try_into().unwrap(),
+                prioritization_fee: *prioritization_fee,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_priority_fee() {
+        let recent = fees(&[50, 0, 10, 40, 30, 20, 0, 0, 0, 0]);
+        assert_eq!(priority_fee(&[], 50, 1_000), 0);
+        assert_eq!(priority_fee(&recent, 0, 1_000), 0);
+        assert_eq!(priority_fee(&recent, 50, 1_000), 0);
+        assert_eq!(priority_fee(&recent, 75, 1_000), 30);
+        assert_eq!(priority_fee(&recent, 100, 1_000), 50);
+        assert_eq!(priority_fee(&recent, 100, 25), 25);
+    }
+
+    #[test]
+    fn test_compute_unit_limit_with_headroom() {
+        assert_eq!(compute_unit_limit_with_headroom(10_000), 12_000);
+        assert_eq!(
+            compute_unit_limit_with_headroom(1_300_000),
+            MAX_COMPUTE_UNIT_LIMIT
+        );
+    }
+
+    #[test]
+    fn test_tx_message_nonce() {
+        let payer = Pubkey::new_unique();
+        let nonce = Pubkey::new_unique();
+        let nonce_authority = Pubkey::new_unique();
+        let durable_nonce = Hash::new_unique();
+        let ixs = with_compute_budget(
+            10_000,
+            1,
+            &[system_instruction::transfer(
+                &payer,
+                &Pubkey::new_unique(),
+                1,
+            )],
+        );
+
+        let msg = tx_message(
+            ixs.clone(),
+            &payer,
+            &durable_nonce,
+            Some(&(nonce, nonce_authority)),
+        );
+        assert_eq!(msg.recent_blockhash, durable_nonce);
+        assert_eq!(msg.account_keys[0], payer);
+        assert_eq!(msg.instructions.len(), ixs.len() + 1);
+        // nonce advance must be the first instruction for the runtime to treat it as a durable nonce tx
+        let advance = &msg.instructions[0];
+        assert_eq!(
+            msg.account_keys[usize::from(advance.program_id_index)],
+            system_program::ID
+        );
+        assert_eq!(
+            bincode::deserialize::<SystemInstruction>(&advance.data).unwrap(),
+            SystemInstruction::AdvanceNonceAccount
+        );
+        assert_eq!(msg.account_keys[usize::from(advance.accounts[0])], nonce);
+        assert_eq!(
+            msg.account_keys[usize::from(advance.accounts[2])],
+            nonce_authority
+        );
+        assert!(msg.is_signer(usize::from(advance.accounts[2])));
+        assert_eq!(
+            msg.account_keys[usize::from(msg.instructions[1].program_id_index)],
+            compute_budget::ID
+        );
+
+        let msg = tx_message(ixs.clone(), &payer, &durable_nonce, None);
+        assert_eq!(msg.instructions.len(), ixs.len());
+        assert_eq!(
+            msg.account_keys[usize::from(msg.instructions[0].program_id_index)],
+            compute_budget::ID
+        );
+    }
+
+    #[test]
+    fn test_pack_instructions() {
+        let payer = Pubkey::new_unique();
+        assert!(pack_instructions(vec![], &payer).is_empty());
+
+        let ixs: Vec<_> = (0..40)
+            .map(|_| system_instruction::transfer(&payer, &Pubkey::new_unique(), 1))
+            .collect();
+        let packed = pack_instructions(ixs.clone(), &payer);
+        assert!(packed.len() > 1);
+        assert!(packed.len() < ixs.len());
+        assert_eq!(packed.concat(), ixs);
+        for txs in packed.iter() {
+            assert!(max_tx_len(txs, &payer) <= PACKET_DATA_SIZE);
+        }
     }
 }